@@ -1,4 +1,6 @@
-use linguist_types::{Heuristics, Languages, VendorPatterns};
+use linguist_types::{
+    CommentSyntaxes, DocumentationPatterns, Heuristics, Languages, VendorPatterns,
+};
 use std::env;
 use std::fs::File;
 use std::io::BufReader;
@@ -40,4 +42,70 @@ fn main() {
     };
     let vendor_json = serde_json::to_vec(&vendor).expect("Failed to serialize vendor to JSON");
     std::fs::write(out_dir.join("vendor.json"), &vendor_json).expect("Failed to write vendor.json");
+
+    // Parse and serialize documentation.yml
+    println!("cargo:rerun-if-changed=definitions/documentation.yml");
+    let documentation: DocumentationPatterns = {
+        let file =
+            File::open("definitions/documentation.yml").expect("Failed to open documentation.yml");
+        let reader = BufReader::new(file);
+        serde_yaml_ng::from_reader(reader).expect("Failed to parse documentation.yml")
+    };
+    let documentation_json =
+        serde_json::to_vec(&documentation).expect("Failed to serialize documentation to JSON");
+    std::fs::write(out_dir.join("documentation.json"), &documentation_json)
+        .expect("Failed to write documentation.json");
+
+    // Parse and serialize comments.yml, making sure it only refers to known languages
+    println!("cargo:rerun-if-changed=definitions/comments.yml");
+    let comments: CommentSyntaxes = {
+        let file = File::open("definitions/comments.yml").expect("Failed to open comments.yml");
+        let reader = BufReader::new(file);
+        serde_yaml_ng::from_reader(reader).expect("Failed to parse comments.yml")
+    };
+    for language in comments.keys() {
+        assert!(
+            languages.contains_key(language),
+            "comments.yml refers to unknown language '{language}'"
+        );
+    }
+    let comments_json =
+        serde_json::to_vec(&comments).expect("Failed to serialize comments to JSON");
+    std::fs::write(out_dir.join("comments.json"), &comments_json)
+        .expect("Failed to write comments.json");
+
+    // Fingerprint the source definitions so caches of detection results can be
+    // invalidated whenever the data (or this crate) changes. The YAML is hashed
+    // rather than the JSON, whose key order isn't stable between builds
+    let mut parts = vec![
+        env::var("CARGO_PKG_VERSION")
+            .expect("CARGO_PKG_VERSION not set")
+            .into_bytes(),
+    ];
+    for definition in [
+        "languages.yml",
+        "heuristics.yml",
+        "vendor.yml",
+        "documentation.yml",
+        "comments.yml",
+    ] {
+        parts.push(
+            std::fs::read(format!("definitions/{definition}"))
+                .unwrap_or_else(|_| panic!("Failed to read {definition}")),
+        );
+    }
+    let data_version = fingerprint(&parts);
+    println!("cargo:rustc-env=LINGUIST_DATA_VERSION={data_version:016x}");
+}
+
+/// 64-bit FNV-1a hash over a sequence of byte slices
+fn fingerprint(parts: &[Vec<u8>]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in parts {
+        for byte in part {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
 }
@@ -0,0 +1,12 @@
+#!/usr/bin/env bash
+set -e
+
+# Regenerates include/linguist.h from src/lib.rs with cbindgen, through the
+# test that otherwise checks it is up to date
+
+SCRIPT_DIR="$(cd "$(dirname "$0")" && pwd)"
+
+cd "$SCRIPT_DIR"
+LINGUIST_UPDATE_HEADER=1 cargo test --test header "$@"
+
+echo "Wrote $SCRIPT_DIR/include/linguist.h"
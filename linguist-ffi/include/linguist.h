@@ -0,0 +1,183 @@
+#ifndef LINGUIST_H
+#define LINGUIST_H
+
+/* Generated by cbindgen from linguist-ffi/src/lib.rs, do not edit by hand. Regenerate with linguist-ffi/generate-header.sh. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// A language definition from languages.yml.
+typedef struct LinguistLanguage LinguistLanguage;
+
+// A list of detected languages, most likely first.
+typedef struct LinguistLanguageList LinguistLanguageList;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Returns the message of the error of the last call on this thread, or
+// `NULL` if it succeeded. The string is valid until the next call on this
+// thread.
+const char *linguist_last_error(void);
+
+// Returns the version of the embedded language definitions.
+const char *linguist_data_version(void);
+
+// Detects the languages of a path by its extension.
+//
+// Returns `NULL` on error, otherwise a list (possibly empty) to be released
+// with `linguist_language_list_free`.
+//
+// # Safety
+//
+// `path` must be null or point to a NUL-terminated string.
+struct LinguistLanguageList *linguist_detect_by_extension(const char *path);
+
+// Detects the languages of a path by its exact filename.
+//
+// Returns `NULL` on error, otherwise a list (possibly empty) to be released
+// with `linguist_language_list_free`.
+//
+// # Safety
+//
+// `path` must be null or point to a NUL-terminated string.
+struct LinguistLanguageList *linguist_detect_by_filename(const char *path);
+
+// Detects the languages of a file from its path and content, trying the
+// filename, then the extension, then content heuristics.
+//
+// Only the first 50 KiB of `content` are inspected. Returns `NULL` on error,
+// otherwise a list (possibly empty) to be released with
+// `linguist_language_list_free`.
+//
+// # Safety
+//
+// `path` must be null or point to a NUL-terminated string, and `content` must
+// be null (with `length` 0) or point to `length` readable bytes.
+struct LinguistLanguageList *linguist_detect(const char *path,
+                                             const uint8_t *content,
+                                             size_t length);
+
+// Applies content heuristics to choose between the languages a path's
+// extension is ambiguous between.
+//
+// Invalid UTF-8 in `content` is replaced before matching. Returns `NULL` on
+// error, otherwise a list (empty if no heuristic matched) to be released with
+// `linguist_language_list_free`.
+//
+// # Safety
+//
+// `path` must be null or point to a NUL-terminated string, and `content` must
+// be null (with `length` 0) or point to `length` readable bytes.
+struct LinguistLanguageList *linguist_disambiguate(const char *path,
+                                                   const uint8_t *content,
+                                                   size_t length);
+
+// Checks whether a path is vendored (third-party) code.
+//
+// Returns 1 if vendored, 0 if not and -1 on error.
+//
+// # Safety
+//
+// `path` must be null or point to a NUL-terminated string.
+int linguist_is_vendored(const char *path);
+
+// Looks up a language by its name or one of its aliases, ignoring case.
+//
+// Returns `NULL` if there is no such language. The handle is owned by the
+// library and must not be freed.
+//
+// # Safety
+//
+// `name` must be null or point to a NUL-terminated string.
+const struct LinguistLanguage *linguist_find_language(const char *name);
+
+// Number of languages in a list, 0 for `NULL`.
+//
+// # Safety
+//
+// `list` must be null or a list that hasn't been freed.
+size_t linguist_language_list_len(const struct LinguistLanguageList *list);
+
+// The language at `index` in a list, or `NULL` if out of range. The handle
+// stays valid after the list is freed.
+//
+// # Safety
+//
+// `list` must be null or a list that hasn't been freed.
+const struct LinguistLanguage *linguist_language_list_get(const struct LinguistLanguageList *list,
+                                                          size_t index);
+
+// Releases a list returned by one of the detection functions. `NULL` is
+// ignored.
+//
+// # Safety
+//
+// `list` must be null or a list that hasn't already been freed.
+void linguist_language_list_free(struct LinguistLanguageList *list);
+
+// The language's name, e.g. "Rust".
+//
+// # Safety
+//
+// `language` must be null or a handle returned by this library.
+const char *linguist_language_name(const struct LinguistLanguage *language);
+
+// The language's type: "data", "programming", "markup" or "prose".
+//
+// # Safety
+//
+// `language` must be null or a handle returned by this library.
+const char *linguist_language_type(const struct LinguistLanguage *language);
+
+// The language's color as "#RRGGBB", or `NULL` if it has none.
+//
+// # Safety
+//
+// `language` must be null or a handle returned by this library.
+const char *linguist_language_color(const struct LinguistLanguage *language);
+
+// The language's Ace editor mode.
+//
+// # Safety
+//
+// `language` must be null or a handle returned by this library.
+const char *linguist_language_ace_mode(const struct LinguistLanguage *language);
+
+// The language's TextMate scope.
+//
+// # Safety
+//
+// `language` must be null or a handle returned by this library.
+const char *linguist_language_tm_scope(const struct LinguistLanguage *language);
+
+// The language's GitHub identifier, or -1 for a `NULL` handle.
+//
+// # Safety
+//
+// `language` must be null or a handle returned by this library.
+int64_t linguist_language_id(const struct LinguistLanguage *language);
+
+// Number of file extensions associated with the language.
+//
+// # Safety
+//
+// `language` must be null or a handle returned by this library.
+size_t linguist_language_extension_count(const struct LinguistLanguage *language);
+
+// The extension at `index` (e.g. ".rs"), or `NULL` if out of range.
+//
+// # Safety
+//
+// `language` must be null or a handle returned by this library.
+const char *linguist_language_extension(const struct LinguistLanguage *language, size_t index);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* LINGUIST_H */
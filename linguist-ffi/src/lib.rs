@@ -0,0 +1,451 @@
+//! C ABI for the linguist crate.
+//!
+//! Exposes detection, disambiguation, vendored checks and language metadata to
+//! non-Rust callers through opaque handles. The C header, `include/linguist.h`,
+//! is generated from this file by cbindgen with `generate-header.sh`, and
+//! `tests/header.rs` checks that it is up to date.
+//!
+//! Conventions:
+//!
+//! - Strings passed in are NUL-terminated UTF-8.
+//! - Functions that can fail return `NULL` (or `-1`) and record a message that
+//!   [`linguist_last_error`] returns, per thread. Every other call clears it.
+//! - Language lists are owned by the caller and must be released with
+//!   [`linguist_language_list_free`]. Language handles and every string
+//!   returned from them are owned by the library and live for the whole
+//!   process, so they are never freed.
+
+use linguist::{DetectedLanguage, LinguistError, definitions};
+use linguist_types::LanguageType;
+use once_cell::sync::Lazy;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// A language definition from languages.yml.
+pub struct LinguistLanguage {
+    name: CString,
+    language_type: CString,
+    color: Option<CString>,
+    ace_mode: CString,
+    tm_scope: CString,
+    language_id: i64,
+    extensions: Vec<CString>,
+}
+
+/// A list of detected languages, most likely first.
+pub struct LinguistLanguageList {
+    languages: Vec<&'static LinguistLanguage>,
+}
+
+/// Handles for every language, built once and shared by all callers
+static LANGUAGES: Lazy<HashMap<&'static str, LinguistLanguage>> = Lazy::new(|| {
+    definitions::LANGUAGES
+        .iter()
+        .map(|(name, language)| {
+            let handle = LinguistLanguage {
+                name: c_string(name),
+                language_type: c_string(type_name(&language.language_type)),
+                color: language.color.as_deref().map(c_string),
+                ace_mode: c_string(&language.ace_mode),
+                tm_scope: c_string(&language.tm_scope),
+                language_id: language.language_id,
+                extensions: language
+                    .extensions
+                    .iter()
+                    .flatten()
+                    .map(|extension| c_string(extension))
+                    .collect(),
+            };
+            (name.as_str(), handle)
+        })
+        .collect()
+});
+
+static DATA_VERSION: Lazy<CString> = Lazy::new(|| c_string(definitions::DATA_VERSION));
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// The name languages.yml uses for a language type
+fn type_name(language_type: &LanguageType) -> &'static str {
+    match language_type {
+        LanguageType::Data => "data",
+        LanguageType::Programming => "programming",
+        LanguageType::Markup => "markup",
+        LanguageType::Prose => "prose",
+    }
+}
+
+/// Converts a string from the definitions, which never contain NUL bytes
+fn c_string(value: &str) -> CString {
+    CString::new(value).unwrap_or_default()
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+fn set_last_error(message: String) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(c_string(&message.replace('\0', ""))));
+}
+
+/// Runs `f`, turning an error or a panic into `fallback` and a recorded message
+fn guard<T>(fallback: T, f: impl FnOnce() -> Result<T, LinguistError>) -> T {
+    clear_last_error();
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(error)) => {
+            set_last_error(error.to_string());
+            fallback
+        }
+        Err(_) => {
+            set_last_error("internal error: detection panicked".to_string());
+            fallback
+        }
+    }
+}
+
+/// Reads a NUL-terminated UTF-8 path argument
+///
+/// # Safety
+///
+/// `path` must be null or point to a NUL-terminated string.
+unsafe fn path_arg<'a>(path: *const c_char) -> Result<&'a str, LinguistError> {
+    if path.is_null() {
+        return Err(LinguistError::InvalidPath("null pointer".to_string()));
+    }
+    // SAFETY: guaranteed by the caller
+    let path = unsafe { CStr::from_ptr(path) };
+    path.to_str()
+        .map_err(|_| LinguistError::InvalidPath(path.to_string_lossy().into_owned()))
+}
+
+/// Borrows a content buffer argument
+///
+/// # Safety
+///
+/// `content` must be null (with `length` 0) or point to `length` readable bytes.
+unsafe fn content_arg<'a>(content: *const u8, length: usize) -> &'a [u8] {
+    if content.is_null() || length == 0 {
+        &[]
+    } else {
+        // SAFETY: guaranteed by the caller
+        unsafe { std::slice::from_raw_parts(content, length) }
+    }
+}
+
+/// Wraps detection results in a list handle for the caller to own
+fn into_list(detected: Vec<DetectedLanguage>) -> *mut LinguistLanguageList {
+    let languages = detected
+        .iter()
+        .filter_map(|language| LANGUAGES.get(language.name))
+        .collect();
+    Box::into_raw(Box::new(LinguistLanguageList { languages }))
+}
+
+/// Returns the message of the error of the last call on this thread, or
+/// `NULL` if it succeeded. The string is valid until the next call on this
+/// thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn linguist_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Returns the version of the embedded language definitions.
+#[unsafe(no_mangle)]
+pub extern "C" fn linguist_data_version() -> *const c_char {
+    clear_last_error();
+    DATA_VERSION.as_ptr()
+}
+
+/// Detects the languages of a path by its extension.
+///
+/// Returns `NULL` on error, otherwise a list (possibly empty) to be released
+/// with `linguist_language_list_free`.
+///
+/// # Safety
+///
+/// `path` must be null or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_detect_by_extension(
+    path: *const c_char,
+) -> *mut LinguistLanguageList {
+    guard(ptr::null_mut(), || {
+        // SAFETY: guaranteed by the caller
+        let path = unsafe { path_arg(path) }?;
+        linguist::detect_language_by_extension(path).map(into_list)
+    })
+}
+
+/// Detects the languages of a path by its exact filename.
+///
+/// Returns `NULL` on error, otherwise a list (possibly empty) to be released
+/// with `linguist_language_list_free`.
+///
+/// # Safety
+///
+/// `path` must be null or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_detect_by_filename(
+    path: *const c_char,
+) -> *mut LinguistLanguageList {
+    guard(ptr::null_mut(), || {
+        // SAFETY: guaranteed by the caller
+        let path = unsafe { path_arg(path) }?;
+        linguist::detect_language_by_filename(path).map(into_list)
+    })
+}
+
+/// Detects the languages of a file from its path and content, trying the
+/// filename, then the extension, then content heuristics.
+///
+/// Only the first 50 KiB of `content` are inspected. Returns `NULL` on error,
+/// otherwise a list (possibly empty) to be released with
+/// `linguist_language_list_free`.
+///
+/// # Safety
+///
+/// `path` must be null or point to a NUL-terminated string, and `content` must
+/// be null (with `length` 0) or point to `length` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_detect(
+    path: *const c_char,
+    content: *const u8,
+    length: usize,
+) -> *mut LinguistLanguageList {
+    guard(ptr::null_mut(), || {
+        // SAFETY: guaranteed by the caller
+        let (path, content) = unsafe { (path_arg(path)?, content_arg(content, length)) };
+        linguist::detect_reader(path, content).map(into_list)
+    })
+}
+
+/// Applies content heuristics to choose between the languages a path's
+/// extension is ambiguous between.
+///
+/// Invalid UTF-8 in `content` is replaced before matching. Returns `NULL` on
+/// error, otherwise a list (empty if no heuristic matched) to be released with
+/// `linguist_language_list_free`.
+///
+/// # Safety
+///
+/// `path` must be null or point to a NUL-terminated string, and `content` must
+/// be null (with `length` 0) or point to `length` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_disambiguate(
+    path: *const c_char,
+    content: *const u8,
+    length: usize,
+) -> *mut LinguistLanguageList {
+    guard(ptr::null_mut(), || {
+        // SAFETY: guaranteed by the caller
+        let (path, content) = unsafe { (path_arg(path)?, content_arg(content, length)) };
+        linguist::disambiguate(path, &String::from_utf8_lossy(content)).map(into_list)
+    })
+}
+
+/// Checks whether a path is vendored (third-party) code.
+///
+/// Returns 1 if vendored, 0 if not and -1 on error.
+///
+/// # Safety
+///
+/// `path` must be null or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_is_vendored(path: *const c_char) -> c_int {
+    guard(-1, || {
+        // SAFETY: guaranteed by the caller
+        let path = unsafe { path_arg(path) }?;
+        linguist::is_vendored(path).map(c_int::from)
+    })
+}
+
+/// Looks up a language by its name or one of its aliases, ignoring case.
+///
+/// Returns `NULL` if there is no such language. The handle is owned by the
+/// library and must not be freed.
+///
+/// # Safety
+///
+/// `name` must be null or point to a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_find_language(name: *const c_char) -> *const LinguistLanguage {
+    guard(ptr::null(), || {
+        // SAFETY: guaranteed by the caller
+        let name = unsafe { path_arg(name) }?;
+        Ok(linguist::find_language(name)
+            .and_then(|language| LANGUAGES.get(language.name))
+            .map_or(ptr::null(), |language| language as *const _))
+    })
+}
+
+/// Number of languages in a list, 0 for `NULL`.
+///
+/// # Safety
+///
+/// `list` must be null or a list that hasn't been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_language_list_len(list: *const LinguistLanguageList) -> usize {
+    clear_last_error();
+    // SAFETY: guaranteed by the caller
+    unsafe { list.as_ref() }.map_or(0, |list| list.languages.len())
+}
+
+/// The language at `index` in a list, or `NULL` if out of range. The handle
+/// stays valid after the list is freed.
+///
+/// # Safety
+///
+/// `list` must be null or a list that hasn't been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_language_list_get(
+    list: *const LinguistLanguageList,
+    index: usize,
+) -> *const LinguistLanguage {
+    clear_last_error();
+    // SAFETY: guaranteed by the caller
+    unsafe { list.as_ref() }
+        .and_then(|list| list.languages.get(index))
+        .map_or(ptr::null(), |language| *language as *const _)
+}
+
+/// Releases a list returned by one of the detection functions. `NULL` is
+/// ignored.
+///
+/// # Safety
+///
+/// `list` must be null or a list that hasn't already been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_language_list_free(list: *mut LinguistLanguageList) {
+    clear_last_error();
+    if !list.is_null() {
+        // SAFETY: the list was created by `into_list` and is freed only once
+        drop(unsafe { Box::from_raw(list) });
+    }
+}
+
+/// Reads a field of a language handle, `NULL` for a `NULL` handle
+///
+/// # Safety
+///
+/// `language` must be null or a handle returned by this library.
+unsafe fn language_field(
+    language: *const LinguistLanguage,
+    field: impl FnOnce(&'static LinguistLanguage) -> Option<&'static CString>,
+) -> *const c_char {
+    clear_last_error();
+    // SAFETY: handles point into `LANGUAGES`, which is never dropped
+    let language: Option<&'static LinguistLanguage> = unsafe { language.as_ref() };
+    language
+        .and_then(field)
+        .map_or(ptr::null(), |value| value.as_ptr())
+}
+
+/// The language's name, e.g. "Rust".
+///
+/// # Safety
+///
+/// `language` must be null or a handle returned by this library.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_language_name(
+    language: *const LinguistLanguage,
+) -> *const c_char {
+    // SAFETY: guaranteed by the caller
+    unsafe { language_field(language, |language| Some(&language.name)) }
+}
+
+/// The language's type: "data", "programming", "markup" or "prose".
+///
+/// # Safety
+///
+/// `language` must be null or a handle returned by this library.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_language_type(
+    language: *const LinguistLanguage,
+) -> *const c_char {
+    // SAFETY: guaranteed by the caller
+    unsafe { language_field(language, |language| Some(&language.language_type)) }
+}
+
+/// The language's color as "#RRGGBB", or `NULL` if it has none.
+///
+/// # Safety
+///
+/// `language` must be null or a handle returned by this library.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_language_color(
+    language: *const LinguistLanguage,
+) -> *const c_char {
+    // SAFETY: guaranteed by the caller
+    unsafe { language_field(language, |language| language.color.as_ref()) }
+}
+
+/// The language's Ace editor mode.
+///
+/// # Safety
+///
+/// `language` must be null or a handle returned by this library.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_language_ace_mode(
+    language: *const LinguistLanguage,
+) -> *const c_char {
+    // SAFETY: guaranteed by the caller
+    unsafe { language_field(language, |language| Some(&language.ace_mode)) }
+}
+
+/// The language's TextMate scope.
+///
+/// # Safety
+///
+/// `language` must be null or a handle returned by this library.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_language_tm_scope(
+    language: *const LinguistLanguage,
+) -> *const c_char {
+    // SAFETY: guaranteed by the caller
+    unsafe { language_field(language, |language| Some(&language.tm_scope)) }
+}
+
+/// The language's GitHub identifier, or -1 for a `NULL` handle.
+///
+/// # Safety
+///
+/// `language` must be null or a handle returned by this library.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_language_id(language: *const LinguistLanguage) -> i64 {
+    clear_last_error();
+    // SAFETY: guaranteed by the caller
+    unsafe { language.as_ref() }.map_or(-1, |language| language.language_id)
+}
+
+/// Number of file extensions associated with the language.
+///
+/// # Safety
+///
+/// `language` must be null or a handle returned by this library.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_language_extension_count(
+    language: *const LinguistLanguage,
+) -> usize {
+    clear_last_error();
+    // SAFETY: guaranteed by the caller
+    unsafe { language.as_ref() }.map_or(0, |language| language.extensions.len())
+}
+
+/// The extension at `index` (e.g. ".rs"), or `NULL` if out of range.
+///
+/// # Safety
+///
+/// `language` must be null or a handle returned by this library.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn linguist_language_extension(
+    language: *const LinguistLanguage,
+    index: usize,
+) -> *const c_char {
+    // SAFETY: guaranteed by the caller
+    unsafe { language_field(language, |language| language.extensions.get(index)) }
+}
@@ -0,0 +1,105 @@
+// Exercises the C API the way a C consumer would.
+//
+// Built and run by tests/c_api.rs; exits non-zero on the first failure.
+
+#include <stdio.h>
+#include <string.h>
+
+#include "linguist.h"
+
+#define CHECK(condition)                                                       \
+    do {                                                                       \
+        if (!(condition)) {                                                    \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__,  \
+                    #condition);                                               \
+            return 1;                                                          \
+        }                                                                      \
+    } while (0)
+
+static int has_language(const LinguistLanguageList *list, const char *name) {
+    for (size_t i = 0; i < linguist_language_list_len(list); i++) {
+        const LinguistLanguage *language = linguist_language_list_get(list, i);
+        if (strcmp(linguist_language_name(language), name) == 0) {
+            return 1;
+        }
+    }
+    return 0;
+}
+
+int main(void) {
+    // Detection by extension and filename
+    LinguistLanguageList *list = linguist_detect_by_extension("src/main.rs");
+    CHECK(list != NULL);
+    CHECK(has_language(list, "Rust"));
+    linguist_language_list_free(list);
+
+    list = linguist_detect_by_filename("Makefile");
+    CHECK(list != NULL);
+    CHECK(linguist_language_list_len(list) == 1);
+    CHECK(strcmp(linguist_language_name(linguist_language_list_get(list, 0)), "Makefile") == 0);
+    CHECK(linguist_language_list_get(list, 1) == NULL);
+    linguist_language_list_free(list);
+
+    // Detection from content
+    const char *header = "#include <iostream>\nclass Foo {};\n";
+    list = linguist_detect("foo.h", (const uint8_t *)header, strlen(header));
+    CHECK(list != NULL);
+    CHECK(linguist_language_list_len(list) == 1);
+    CHECK(has_language(list, "C++"));
+    linguist_language_list_free(list);
+
+    const char *script = "#!/usr/bin/env ruby\nputs 'Hello'\n";
+    list = linguist_disambiguate("script.rb", (const uint8_t *)script, strlen(script));
+    CHECK(list != NULL);
+    linguist_language_list_free(list);
+
+    // Unknown files give an empty list, not an error
+    list = linguist_detect_by_extension("file.not-a-real-extension");
+    CHECK(list != NULL);
+    CHECK(linguist_language_list_len(list) == 0);
+    linguist_language_list_free(list);
+
+    // Vendored paths
+    CHECK(linguist_is_vendored("node_modules/left-pad/index.js") == 1);
+    CHECK(linguist_is_vendored("src/main.rs") == 0);
+
+    // Errors are reported through linguist_last_error
+    CHECK(linguist_detect_by_extension(NULL) == NULL);
+    CHECK(linguist_last_error() != NULL);
+    CHECK(strstr(linguist_last_error(), "Invalid path") != NULL);
+    CHECK(linguist_is_vendored(NULL) == -1);
+
+    // and cleared by the next call
+    CHECK(linguist_is_vendored("src/main.rs") == 0);
+    CHECK(linguist_last_error() == NULL);
+    CHECK(linguist_detect_by_filename(NULL) == NULL);
+    CHECK(linguist_language_list_len(NULL) == 0);
+    CHECK(linguist_last_error() == NULL);
+
+    // Language metadata
+    const LinguistLanguage *rust = linguist_find_language("rust");
+    CHECK(rust != NULL);
+    CHECK(strcmp(linguist_language_name(rust), "Rust") == 0);
+    CHECK(strcmp(linguist_language_type(rust), "programming") == 0);
+    CHECK(linguist_language_color(rust) != NULL);
+    CHECK(linguist_language_color(rust)[0] == '#');
+    CHECK(linguist_language_id(rust) > 0);
+    CHECK(strlen(linguist_language_tm_scope(rust)) > 0);
+    CHECK(strlen(linguist_language_ace_mode(rust)) > 0);
+
+    int has_rs = 0;
+    for (size_t i = 0; i < linguist_language_extension_count(rust); i++) {
+        has_rs |= strcmp(linguist_language_extension(rust, i), ".rs") == 0;
+    }
+    CHECK(has_rs);
+    CHECK(linguist_language_extension(rust, 1000) == NULL);
+
+    CHECK(strcmp(linguist_language_name(linguist_find_language("js")), "JavaScript") == 0);
+    CHECK(linguist_find_language("not a language") == NULL);
+    CHECK(linguist_language_name(NULL) == NULL);
+    CHECK(linguist_language_id(NULL) == -1);
+    CHECK(strlen(linguist_data_version()) > 0);
+
+    printf("ok\n");
+    return 0;
+}
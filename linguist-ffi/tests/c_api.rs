@@ -0,0 +1,68 @@
+mod test_c_api {
+    use std::env;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    /// Helper: Directory holding the libraries cargo built for this profile
+    fn library_dir() -> PathBuf {
+        // Test binaries live in target/<profile>/deps
+        let exe = env::current_exe().unwrap();
+        exe.parent().unwrap().parent().unwrap().to_path_buf()
+    }
+
+    /// Helper: System libraries a Rust static library needs, as reported by
+    /// `rustc --print native-static-libs`
+    fn native_static_libs() -> Vec<String> {
+        let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+        let source = out_dir.join("empty.rs");
+        std::fs::write(&source, "").unwrap();
+
+        let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        let output = Command::new(rustc)
+            .args(["--crate-type", "staticlib", "--print", "native-static-libs"])
+            .arg("-o")
+            .arg(out_dir.join("libempty.a"))
+            .arg(&source)
+            .output()
+            .expect("Failed to run rustc");
+        assert!(output.status.success(), "rustc failed");
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        stderr
+            .lines()
+            .find_map(|line| line.split_once("native-static-libs: "))
+            .map(|(_, libs)| libs.split_whitespace().map(String::from).collect())
+            .expect("rustc should print native-static-libs")
+    }
+
+    #[test]
+    fn c_program_uses_the_generated_header() {
+        let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let library = library_dir().join("liblinguist_ffi.a");
+        assert!(library.exists(), "Missing {}", library.display());
+
+        let program = Path::new(env!("CARGO_TARGET_TMPDIR")).join("test_linguist");
+        let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+        let status = Command::new(compiler)
+            .arg("-Wall")
+            .arg("-Werror")
+            .arg("-I")
+            .arg(crate_dir.join("include"))
+            .arg(crate_dir.join("tests/c/test_linguist.c"))
+            .arg(&library)
+            .args(native_static_libs())
+            .arg("-o")
+            .arg(&program)
+            .status()
+            .expect("Failed to run the C compiler");
+        assert!(status.success(), "Compiling the C test program failed");
+
+        let output = Command::new(&program).output().unwrap();
+        assert!(
+            output.status.success(),
+            "C test program failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+    }
+}
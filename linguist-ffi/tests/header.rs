@@ -0,0 +1,34 @@
+mod test_header {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    /// Set to rewrite the header instead of checking it, see generate-header.sh
+    const UPDATE_VAR: &str = "LINGUIST_UPDATE_HEADER";
+
+    #[test]
+    fn header_is_up_to_date() {
+        let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+            .expect("Failed to read cbindgen.toml");
+        let mut generated = Vec::new();
+        cbindgen::Builder::new()
+            .with_crate(crate_dir)
+            .with_config(config)
+            .generate()
+            .expect("Failed to generate C header")
+            .write(&mut generated);
+
+        let header = crate_dir.join("include/linguist.h");
+        if env::var_os(UPDATE_VAR).is_some() {
+            fs::write(&header, &generated).unwrap();
+            return;
+        }
+        let checked_in = fs::read(&header).unwrap_or_default();
+        assert!(
+            checked_in == generated,
+            "{} is out of date, run linguist-ffi/generate-header.sh",
+            header.display()
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Python bindings for the linguist crate.
+//!
+//! Built with maturin into a `linguist` extension module. Detection runs with
+//! the GIL released, so threads in a Python process can detect files in
+//! parallel.
+
+use linguist::{DEFAULT_READ_LIMIT, DetectedLanguage, definitions};
+use linguist_types::LanguageType;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyOSError};
+use pyo3::prelude::*;
+use std::fs::File;
+use std::path::PathBuf;
+
+create_exception!(
+    linguist,
+    LinguistError,
+    PyException,
+    "Raised when detection fails, e.g. for an invalid path."
+);
+
+/// Maps a detection error to a Python exception
+fn to_py_err(error: linguist::LinguistError) -> PyErr {
+    match error {
+        linguist::LinguistError::Io(message) => PyOSError::new_err(message),
+        error => LinguistError::new_err(error.to_string()),
+    }
+}
+
+/// A language definition from languages.yml.
+#[pyclass(frozen, module = "linguist")]
+struct Language {
+    name: &'static str,
+    definition: &'static linguist_types::Language,
+}
+
+impl From<DetectedLanguage> for Language {
+    fn from(detected: DetectedLanguage) -> Self {
+        Language {
+            name: detected.name,
+            definition: detected.definition,
+        }
+    }
+}
+
+#[pymethods]
+impl Language {
+    /// The language's name, e.g. "Rust".
+    #[getter]
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// "data", "programming", "markup" or "prose".
+    #[getter]
+    fn r#type(&self) -> &'static str {
+        match self.definition.language_type {
+            LanguageType::Data => "data",
+            LanguageType::Programming => "programming",
+            LanguageType::Markup => "markup",
+            LanguageType::Prose => "prose",
+        }
+    }
+
+    /// Color as "#RRGGBB", or None.
+    #[getter]
+    fn color(&self) -> Option<&'static str> {
+        self.definition.color.as_deref()
+    }
+
+    /// File extensions, e.g. [".rs", ".rs.in"].
+    #[getter]
+    fn extensions(&self) -> Vec<&'static str> {
+        strings(&self.definition.extensions)
+    }
+
+    /// Exact filenames, e.g. ["Makefile"].
+    #[getter]
+    fn filenames(&self) -> Vec<&'static str> {
+        strings(&self.definition.filenames)
+    }
+
+    /// Alternative names the language can be looked up by.
+    #[getter]
+    fn aliases(&self) -> Vec<&'static str> {
+        strings(&self.definition.aliases)
+    }
+
+    /// Programs that execute the language, used to match shebangs.
+    #[getter]
+    fn interpreters(&self) -> Vec<&'static str> {
+        strings(&self.definition.interpreters)
+    }
+
+    /// Parent language used to group statistics, or None.
+    #[getter]
+    fn group(&self) -> Option<&'static str> {
+        self.definition.group.as_deref()
+    }
+
+    /// GitHub's identifier for the language.
+    #[getter]
+    fn language_id(&self) -> i64 {
+        self.definition.language_id
+    }
+
+    /// TextMate scope.
+    #[getter]
+    fn tm_scope(&self) -> &'static str {
+        &self.definition.tm_scope
+    }
+
+    /// Ace editor mode.
+    #[getter]
+    fn ace_mode(&self) -> &'static str {
+        &self.definition.ace_mode
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Language({:?})", self.name)
+    }
+
+    fn __eq__(&self, other: &Language) -> bool {
+        self.name == other.name
+    }
+
+    fn __hash__(&self) -> u64 {
+        self.definition.language_id as u64
+    }
+}
+
+fn strings(values: &'static Option<Vec<String>>) -> Vec<&'static str> {
+    values.iter().flatten().map(String::as_str).collect()
+}
+
+fn languages(detected: Vec<DetectedLanguage>) -> Vec<Language> {
+    detected.into_iter().map(Language::from).collect()
+}
+
+/// Detects the languages of a file, trying its filename, then its extension,
+/// then content heuristics.
+///
+/// If `content` is None the file is read from disk. At most `read_limit`
+/// bytes of content are inspected. Returns the candidate languages, most
+/// likely first, or an empty list if the file isn't recognised.
+#[pyfunction]
+#[pyo3(signature = (path, content=None, read_limit=DEFAULT_READ_LIMIT))]
+fn detect(
+    py: Python<'_>,
+    path: PathBuf,
+    content: Option<&[u8]>,
+    read_limit: usize,
+) -> PyResult<Vec<Language>> {
+    py.detach(|| match content {
+        Some(content) => linguist::detect_reader_with_limit(&path, content, read_limit),
+        None => File::open(&path)
+            .map_err(linguist::LinguistError::from)
+            .and_then(|file| linguist::detect_reader_with_limit(&path, file, read_limit)),
+    })
+    .map(languages)
+    .map_err(to_py_err)
+}
+
+/// Detects the languages of a path by its extension alone.
+#[pyfunction]
+fn detect_language_by_extension(py: Python<'_>, path: PathBuf) -> PyResult<Vec<Language>> {
+    py.detach(|| linguist::detect_language_by_extension(&path))
+        .map(languages)
+        .map_err(to_py_err)
+}
+
+/// Detects the languages of a path by its exact filename alone.
+#[pyfunction]
+fn detect_language_by_filename(py: Python<'_>, path: PathBuf) -> PyResult<Vec<Language>> {
+    py.detach(|| linguist::detect_language_by_filename(&path))
+        .map(languages)
+        .map_err(to_py_err)
+}
+
+/// Applies content heuristics to choose between the languages a path's
+/// extension is ambiguous between. Returns an empty list if no heuristic
+/// matched.
+#[pyfunction]
+fn disambiguate(py: Python<'_>, path: PathBuf, content: &str) -> PyResult<Vec<Language>> {
+    py.detach(|| linguist::disambiguate(&path, content))
+        .map(languages)
+        .map_err(to_py_err)
+}
+
+/// Whether a path is vendored (third-party) code.
+#[pyfunction]
+fn is_vendored(py: Python<'_>, path: PathBuf) -> PyResult<bool> {
+    py.detach(|| linguist::is_vendored(&path))
+        .map_err(to_py_err)
+}
+
+/// Looks up a language by its name or one of its aliases, ignoring case.
+/// Returns None if there is no such language.
+#[pyfunction]
+fn find_language(name: &str) -> Option<Language> {
+    linguist::find_language(name).map(Language::from)
+}
+
+#[pymodule]
+#[pyo3(name = "linguist")]
+fn linguist_python(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add("DATA_VERSION", definitions::DATA_VERSION)?;
+    module.add("DEFAULT_READ_LIMIT", DEFAULT_READ_LIMIT)?;
+    module.add("LinguistError", module.py().get_type::<LinguistError>())?;
+    module.add_class::<Language>()?;
+    module.add_function(wrap_pyfunction!(detect, module)?)?;
+    module.add_function(wrap_pyfunction!(detect_language_by_extension, module)?)?;
+    module.add_function(wrap_pyfunction!(detect_language_by_filename, module)?)?;
+    module.add_function(wrap_pyfunction!(disambiguate, module)?)?;
+    module.add_function(wrap_pyfunction!(is_vendored, module)?)?;
+    module.add_function(wrap_pyfunction!(find_language, module)?)?;
+    Ok(())
+}
@@ -0,0 +1,98 @@
+import threading
+
+import pytest
+
+import linguist
+
+
+def names(languages):
+    return [language.name for language in languages]
+
+
+class TestDetect:
+    def test_detects_by_extension(self):
+        assert names(linguist.detect("script.py", b"")) == ["Python"]
+
+    def test_detects_by_filename(self):
+        assert names(linguist.detect("Makefile", b"")) == ["Makefile"]
+
+    def test_disambiguates_by_content(self):
+        content = b"#include <iostream>\nclass Foo {};\n"
+        assert names(linguist.detect("foo.h", content)) == ["C++"]
+
+    def test_reads_the_file_without_content(self, tmp_path):
+        path = tmp_path / "foo.h"
+        path.write_text("#include <iostream>\nclass Foo {};\n")
+        assert names(linguist.detect(str(path))) == ["C++"]
+        assert names(linguist.detect(path)) == ["C++"]
+
+    def test_missing_file_raises_os_error(self, tmp_path):
+        with pytest.raises(OSError):
+            linguist.detect(tmp_path / "missing.h")
+
+    def test_unknown_files_give_an_empty_list(self):
+        assert linguist.detect("file.not-a-real-extension", b"") == []
+
+    def test_read_limit_is_respected(self):
+        content = b"\n" * 100 + b"#include <iostream>\nclass Foo {};\n"
+        assert "C++" not in names(linguist.detect("foo.h", content, read_limit=10))
+
+    def test_can_run_on_many_threads(self):
+        results = []
+
+        def worker():
+            for _ in range(50):
+                results.append(names(linguist.detect("foo.h", b"#include <iostream>\n")))
+
+        threads = [threading.Thread(target=worker) for _ in range(4)]
+        for thread in threads:
+            thread.start()
+        for thread in threads:
+            thread.join()
+        assert results == [["C++"]] * 200
+
+
+class TestLookups:
+    def test_detect_language_by_extension(self):
+        assert "Rust" in names(linguist.detect_language_by_extension("lib.rs"))
+
+    def test_detect_language_by_filename(self):
+        assert names(linguist.detect_language_by_filename("Dockerfile")) == ["Dockerfile"]
+
+    def test_disambiguate(self):
+        languages = linguist.disambiguate("foo.h", "#include <iostream>\nclass Foo {};\n")
+        assert names(languages) == ["C++"]
+
+    def test_is_vendored(self):
+        assert linguist.is_vendored("node_modules/left-pad/index.js")
+        assert not linguist.is_vendored("src/main.rs")
+
+    def test_invalid_paths_raise_linguist_error(self):
+        with pytest.raises(linguist.LinguistError):
+            linguist.detect_language_by_extension("")
+
+
+class TestLanguage:
+    def test_metadata(self):
+        rust = linguist.find_language("rust")
+        assert rust.name == "Rust"
+        assert rust.type == "programming"
+        assert rust.color.startswith("#")
+        assert ".rs" in rust.extensions
+        assert rust.language_id > 0
+        assert rust.tm_scope
+        assert rust.ace_mode
+
+    def test_aliases(self):
+        assert linguist.find_language("js").name == "JavaScript"
+        assert linguist.find_language("not a language") is None
+
+    def test_equality_and_hashing(self):
+        detected = linguist.detect("main.rs", b"fn main() {}\n")[0]
+        assert detected == linguist.find_language("Rust")
+        assert len({detected, linguist.find_language("rust")}) == 1
+        assert repr(detected) == 'Language("Rust")'
+
+    def test_module_constants(self):
+        assert linguist.DATA_VERSION
+        assert linguist.DEFAULT_READ_LIMIT == 50 * 1024
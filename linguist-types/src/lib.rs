@@ -184,3 +184,38 @@ pub struct Heuristics {
 
 /// List of regex patterns for vendored files (vendor.yml)
 pub type VendorPatterns = Vec<String>;
+
+/// List of regex patterns for documentation files (documentation.yml)
+pub type DocumentationPatterns = Vec<String>;
+
+// ============================================================================
+// Comment syntax types
+// ============================================================================
+
+/// Comment and string syntax of a language, from comments.yml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommentSyntax {
+    /// Markers starting a comment that runs to the end of the line
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub line_comments: Vec<String>,
+
+    /// Pairs of (open, close) markers delimiting block comments
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub block_comments: Vec<(String, String)>,
+
+    /// Whether block comments nest
+    #[serde(default)]
+    pub nested: bool,
+
+    /// Delimiters of string literals that end at the end of the line
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub strings: Vec<String>,
+
+    /// Delimiters of string literals that may span several lines
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub multiline_strings: Vec<String>,
+}
+
+/// The root type of comments.yml
+/// Maps language names to their comment syntax
+pub type CommentSyntaxes = HashMap<String, CommentSyntax>;
@@ -0,0 +1,364 @@
+//! Language breakdowns of zip and tar archives, read without extracting them.
+//!
+//! Entries are classified by their path inside the archive from the first
+//! [`ScanOptions::read_limit`] bytes of each. Line counts and embedded
+//! languages, if requested, are computed while the rest of the entry streams
+//! past; only files split into regions (HTML, Markdown...) are held in memory
+//! in full, as in directory scans. Tar archives may be gzip or xz compressed
+//! and are read as a stream.
+//!
+//! Entries are subject to the same rules as the files of a directory: vendor
+//! patterns, `.gitattributes` overrides in the archive and
+//! [`ScanOptions::overrides`]. Entries that are binary or generated, judging by
+//! their path and the prefix read (see [`crate::generated`]), are left out of
+//! the breakdown altogether: archives often carry build output next to the
+//! sources.
+//!
+//! [`repository::walk`](crate::repository::walk) and
+//! [`repository::analyze`](crate::repository::analyze) accept an archive in
+//! place of a directory, so most callers never need this module directly.
+
+use crate::generated::{is_binary, is_generated};
+use crate::gitattributes::GitAttributes;
+use crate::lines::CountingReader;
+use crate::repository::{
+    Breakdown, FileEntry, ScanOptions, classify_reader, find_embedded, may_embed,
+};
+use crate::{LinguistError, Result};
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use xz2::read::XzDecoder;
+use zip::ZipArchive;
+use zip::result::ZipError;
+
+/// First bytes of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// First bytes of an xz stream
+const XZ_MAGIC: [u8; 6] = [0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+/// First bytes of a zip archive, which start with a local file header or,
+/// when empty, the end of central directory record
+const ZIP_MAGIC: [[u8; 4]; 2] = [*b"PK\x03\x04", *b"PK\x05\x06"];
+
+/// File name suffixes of the archives recognized by [`is_archive`]
+const ARCHIVE_SUFFIXES: [&str; 6] = [".zip", ".tar", ".tar.gz", ".tgz", ".tar.xz", ".txz"];
+
+/// Whether a path names an archive that can be analyzed, judging by its
+/// file name.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::archive::is_archive;
+///
+/// assert!(is_archive("vendor-drop.tar.xz"));
+/// assert!(is_archive("Sources.ZIP"));
+/// assert!(!is_archive("src/main.rs"));
+/// ```
+pub fn is_archive<P: AsRef<Path>>(path: P) -> bool {
+    let Some(name) = path.as_ref().file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    let name = name.to_ascii_lowercase();
+    ARCHIVE_SUFFIXES
+        .iter()
+        .any(|suffix| name.len() > suffix.len() && name.ends_with(suffix))
+}
+
+/// Builds the language breakdown of a zip or tar archive on disk.
+///
+/// The format is recognized from the content rather than the file name, see
+/// [`analyze_zip`] and [`analyze_tar`].
+pub fn analyze_archive<P: AsRef<Path>>(path: P, options: &ScanOptions) -> Result<Breakdown> {
+    let mut file = File::open(path)?;
+    if is_zip(&mut file)? {
+        analyze_zip(file, options)
+    } else {
+        analyze_tar(file, options)
+    }
+}
+
+/// Lists the files in a zip or tar archive on disk, relative to the archive
+/// root and sorted, the same way [`repository::walk`](crate::repository::walk)
+/// lists a directory.
+pub fn walk_archive<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>> {
+    let mut file = File::open(path)?;
+    let mut paths = Vec::new();
+    if is_zip(&mut file)? {
+        let archive = ZipArchive::new(file).map_err(zip_error)?;
+        for index in 0..archive.len() {
+            let name = archive.name_for_index(index).unwrap_or_default();
+            if !name.ends_with('/')
+                && let Some(path) = entry_path(Path::new(name))
+            {
+                paths.push(path);
+            }
+        }
+    } else {
+        let mut archive = tar::Archive::new(decompress(file)?);
+        for entry in archive.entries().map_err(archive_error)? {
+            let entry = entry.map_err(archive_error)?;
+            if entry.header().entry_type().is_file()
+                && let Some(path) = entry_path(&entry.path().map_err(archive_error)?)
+            {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Whether a file starts like a zip archive, leaving it at its start
+fn is_zip<R: Read + Seek>(reader: &mut R) -> Result<bool> {
+    let mut magic = [0; 4];
+    let mut read = 0;
+    while read < magic.len() {
+        match reader.read(&mut magic[read..])? {
+            0 => break,
+            n => read += n,
+        }
+    }
+    reader.seek(SeekFrom::Start(0))?;
+    Ok(read == magic.len() && ZIP_MAGIC.contains(&magic))
+}
+
+/// Builds the language breakdown of a tar archive, which may be gzip or xz
+/// compressed.
+///
+/// Overrides from `.gitattributes` files in the archive are applied to every
+/// entry. Line counts and embedded languages are computed as each entry is
+/// read, so they only see the overrides of `.gitattributes` files that came
+/// before it in the archive, which is always the case for `git archive`.
+/// Entries that fail to classify are recorded in [`Breakdown::errors`]; an
+/// archive that can't be read at all is an error.
+///
+/// # Examples
+///
+/// ```no_run
+/// use linguist::archive::analyze_tar;
+/// use linguist::repository::ScanOptions;
+/// use std::fs::File;
+///
+/// let breakdown = analyze_tar(File::open("release.tar.gz")?, &ScanOptions::default())?;
+/// for (language, stats) in breakdown.sorted_languages() {
+///     println!("{language}: {} bytes", stats.bytes);
+/// }
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn analyze_tar<R: Read>(reader: R, options: &ScanOptions) -> Result<Breakdown> {
+    let mut archive = tar::Archive::new(decompress(reader)?);
+    let mut scan = Scan::new(options);
+
+    for entry in archive.entries().map_err(archive_error)? {
+        let mut entry = entry.map_err(archive_error)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let Some(path) = entry_path(&entry.path().map_err(archive_error)?) else {
+            continue;
+        };
+
+        if is_gitattributes(&path) {
+            scan.add_gitattributes(&path, &mut entry)?;
+        } else {
+            let size = entry.size();
+            scan.add(path, size, &mut entry, options);
+        }
+    }
+    Ok(scan.finish())
+}
+
+/// Builds the language breakdown of a zip archive.
+///
+/// The central directory of a zip archive lists every entry up front, so
+/// unlike [`analyze_tar`] all `.gitattributes` overrides are known before any
+/// entry is read. Entries that can't be read, e.g. because they are
+/// encrypted, are recorded in [`Breakdown::errors`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use linguist::archive::analyze_zip;
+/// use linguist::repository::ScanOptions;
+/// use std::fs::File;
+///
+/// let breakdown = analyze_zip(File::open("sources.zip")?, &ScanOptions::default())?;
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn analyze_zip<R: Read + Seek>(reader: R, options: &ScanOptions) -> Result<Breakdown> {
+    let mut archive = ZipArchive::new(reader).map_err(zip_error)?;
+    let mut scan = Scan::new(options);
+
+    // Directories are only listed by name, with a trailing slash
+    let mut files = Vec::new();
+    for index in 0..archive.len() {
+        let name = archive.name_for_index(index).unwrap_or_default();
+        if !name.ends_with('/')
+            && let Some(path) = entry_path(Path::new(name))
+        {
+            files.push((index, path));
+        }
+    }
+
+    for (index, path) in files.iter().filter(|(_, path)| is_gitattributes(path)) {
+        let mut file = archive.by_index(*index).map_err(zip_error)?;
+        scan.add_gitattributes(path, &mut file)?;
+    }
+
+    for (index, path) in files {
+        if is_gitattributes(&path) {
+            continue;
+        }
+        match archive.by_index(index) {
+            Ok(mut file) => {
+                let size = file.size();
+                scan.add(path, size, &mut file, options);
+            }
+            Err(e) => scan.results.push((path, Err(zip_error(e)))),
+        }
+    }
+    Ok(scan.finish())
+}
+
+/// Wraps a tar stream in a decoder if it is gzip or xz compressed
+fn decompress<'a, R: Read + 'a>(reader: R) -> Result<Box<dyn Read + 'a>> {
+    let mut reader = BufReader::new(reader);
+    let magic = reader.fill_buf()?;
+    if magic.starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(GzDecoder::new(reader)))
+    } else if magic.starts_with(&XZ_MAGIC) {
+        Ok(Box::new(XzDecoder::new(reader)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// The entries of an archive classified so far, and the `.gitattributes`
+/// overrides seen so far
+struct Scan {
+    attributes: GitAttributes,
+    results: Vec<(PathBuf, Result<FileEntry>)>,
+}
+
+impl Scan {
+    fn new(options: &ScanOptions) -> Self {
+        Scan {
+            attributes: options.overrides.clone(),
+            results: Vec::new(),
+        }
+    }
+
+    fn add_gitattributes<R: Read>(&mut self, path: &Path, reader: &mut R) -> Result<()> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content)?;
+        self.attributes
+            .add_file(path.parent().unwrap_or(Path::new("")), &content);
+        Ok(())
+    }
+
+    fn add<R: Read>(&mut self, path: PathBuf, size: u64, reader: R, options: &ScanOptions) {
+        if !options.filter.matches(&path) {
+            return;
+        }
+        match scan_entry(&path, size, reader, options, &self.attributes) {
+            Ok(Some(entry)) => self.results.push((path, Ok(entry))),
+            Ok(None) => {}
+            Err(e) => self.results.push((path, Err(e))),
+        }
+    }
+
+    /// Applies every override now that all `.gitattributes` files have been
+    /// seen, and builds the breakdown in path order
+    fn finish(mut self) -> Breakdown {
+        self.results.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut breakdown = Breakdown::default();
+        for (path, result) in self.results {
+            match result {
+                Ok(mut entry) => {
+                    self.attributes.apply(&mut entry);
+                    breakdown.add(entry);
+                }
+                Err(e) => breakdown.errors.push((path, e)),
+            }
+        }
+        breakdown
+    }
+}
+
+fn is_gitattributes(path: &Path) -> bool {
+    path.file_name() == Some(".gitattributes".as_ref())
+}
+
+/// Classifies one entry from a prefix of its content, or returns `None` if
+/// the entry is binary or generated. Line counts and embedded languages are
+/// taken from the rest of the entry as it is read, in one pass, without
+/// holding it in memory unless it is split into regions.
+fn scan_entry<R: Read>(
+    path: &Path,
+    size: u64,
+    mut reader: R,
+    options: &ScanOptions,
+    attributes: &GitAttributes,
+) -> Result<Option<FileEntry>> {
+    let mut prefix = Vec::new();
+    reader
+        .by_ref()
+        .take(options.read_limit as u64)
+        .read_to_end(&mut prefix)?;
+    if is_binary(&prefix) || is_generated(path, &prefix) {
+        return Ok(None);
+    }
+    let mut entry = classify_reader(path, size, prefix.as_slice(), options)?;
+    if !options.count_lines && !options.embedded_languages {
+        return Ok(Some(entry));
+    }
+
+    // Overrides are applied once the whole archive has been read, but those
+    // seen so far already decide the language to count lines in
+    let Some(language) = attributes.language(path).or(entry.language) else {
+        return Ok(Some(entry));
+    };
+    let embeds = options.embedded_languages && may_embed(language);
+    let content = prefix.as_slice().chain(reader);
+
+    if options.count_lines {
+        let mut content = CountingReader::new(content, language);
+        if embeds {
+            entry.embedded = find_embedded(language, &mut content)?;
+        }
+        entry.lines = Some(content.finish()?);
+    } else if embeds {
+        entry.embedded = find_embedded(language, content)?;
+    }
+    Ok(Some(entry))
+}
+
+/// Normalizes an entry's path to be relative to the archive root, skipping
+/// anything inside `.git` or escaping the root
+fn entry_path(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) if part == ".git" => return None,
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!normalized.as_os_str().is_empty()).then_some(normalized)
+}
+
+fn archive_error(error: std::io::Error) -> LinguistError {
+    LinguistError::InvalidArchive(error.to_string())
+}
+
+fn zip_error(error: ZipError) -> LinguistError {
+    match error {
+        ZipError::Io(error) => archive_error(error),
+        error => LinguistError::InvalidArchive(error.to_string()),
+    }
+}
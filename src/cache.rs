@@ -0,0 +1,226 @@
+//! On-disk cache of per-file detection results.
+//!
+//! Entries are keyed by the file's path relative to the repository root and
+//! are only reused while the file's size and modification time are unchanged.
+//! The whole cache is discarded when the embedded definitions
+//! ([`definitions::DATA_VERSION`]), the scanned root, the scan options or the
+//! repository's `.gitattributes` differ from those it was written with.
+
+use crate::gitattributes::GitAttributes;
+use crate::lines::LineCounts;
+use crate::repository::{EmbeddedBytes, FileEntry, ScanOptions};
+use crate::{Result, definitions};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Name of the cache file written inside the cache directory
+const CACHE_FILENAME: &str = "results.json";
+
+/// Numbers the temporary files of this process, together with its pid
+static TEMPORARY_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Cache of detection results for a single repository root.
+///
+/// Lookups are served from the results loaded from disk, while inserts build
+/// up the results of the current scan. Only the latter are saved, so files
+/// that no longer exist drop out of the cache automatically.
+#[derive(Debug)]
+pub struct Cache {
+    path: PathBuf,
+    header: CacheHeader,
+    previous: HashMap<PathBuf, CachedEntry>,
+    current: HashMap<PathBuf, CachedEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CacheHeader {
+    data_version: String,
+    root: PathBuf,
+    read_limit: usize,
+    backtrack_limit: usize,
+    max_content_len: usize,
+    time_budget: Option<Duration>,
+    strategies: Option<Vec<String>>,
+    count_lines: bool,
+    embedded_languages: bool,
+    generated_markers: bool,
+    attributes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedEntry {
+    size: u64,
+    modified: (u64, u32),
+    language: Option<String>,
+    vendored: bool,
+    documentation: bool,
+    generated: bool,
+    lines: Option<LineCounts>,
+    embedded: Vec<(String, u64)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    header: CacheHeader,
+    entries: HashMap<PathBuf, CachedEntry>,
+}
+
+impl Cache {
+    /// Returns the default cache directory for a repository, `.git/linguist-cache`,
+    /// or `None` if `root` has no `.git` directory.
+    pub fn default_dir<P: AsRef<Path>>(root: P) -> Option<PathBuf> {
+        let git_dir = root.as_ref().join(".git");
+        git_dir.is_dir().then(|| git_dir.join("linguist-cache"))
+    }
+
+    /// Loads the cache stored in `dir` for scans of `root` with the given
+    /// options and `.gitattributes` overrides.
+    ///
+    /// A missing, unreadable or outdated cache is not an error; it simply
+    /// starts out empty.
+    pub fn load<P: AsRef<Path>>(
+        dir: P,
+        root: &Path,
+        options: &ScanOptions,
+        attributes: &GitAttributes,
+    ) -> Self {
+        let header = CacheHeader {
+            data_version: definitions::DATA_VERSION.to_string(),
+            root: root.canonicalize().unwrap_or_else(|_| root.to_path_buf()),
+            read_limit: options.read_limit,
+            backtrack_limit: options.heuristic_limits.backtrack_limit,
+            max_content_len: options.heuristic_limits.max_content_len,
+            time_budget: options.heuristic_limits.time_budget,
+            strategies: options
+                .pipeline
+                .as_ref()
+                .map(|pipeline| pipeline.names().into_iter().map(String::from).collect()),
+            count_lines: options.count_lines,
+            embedded_languages: options.embedded_languages,
+            generated_markers: options.generated_markers,
+            attributes: attributes.fingerprint(),
+        };
+        let path = dir.as_ref().join(CACHE_FILENAME);
+
+        let previous = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice::<CacheFile>(&data).ok())
+            .filter(|file| file.header == header)
+            .map(|file| file.entries)
+            .unwrap_or_default();
+
+        Cache {
+            path,
+            header,
+            previous,
+            current: HashMap::new(),
+        }
+    }
+
+    /// Looks up the cached result for a file, if its size and modification time
+    /// still match `metadata`.
+    pub fn get(&self, relative: &Path, metadata: &Metadata) -> Option<FileEntry> {
+        let cached = self.previous.get(relative)?;
+        if cached.size != metadata.len() || Some(cached.modified) != modified(metadata) {
+            return None;
+        }
+
+        // Languages are stored by name, map them back to the static definitions
+        let language = match &cached.language {
+            Some(name) => Some(static_language(name)?),
+            None => None,
+        };
+        let embedded = cached
+            .embedded
+            .iter()
+            .map(|(name, bytes)| {
+                Some(EmbeddedBytes {
+                    language: static_language(name)?,
+                    bytes: *bytes,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(FileEntry {
+            path: relative.to_path_buf(),
+            size: cached.size,
+            language,
+            vendored: cached.vendored,
+            documentation: cached.documentation,
+            generated: cached.generated,
+            lines: cached.lines,
+            embedded,
+        })
+    }
+
+    /// Records the result for a file, stamped with the metadata it was
+    /// classified with.
+    pub fn insert(&mut self, entry: &FileEntry, metadata: &Metadata) {
+        // Without a modification time there's no way to tell when it's stale
+        let Some(modified) = modified(metadata) else {
+            return;
+        };
+
+        self.current.insert(
+            entry.path.clone(),
+            CachedEntry {
+                size: metadata.len(),
+                modified,
+                language: entry.language.map(str::to_string),
+                vendored: entry.vendored,
+                documentation: entry.documentation,
+                generated: entry.generated,
+                lines: entry.lines,
+                embedded: entry
+                    .embedded
+                    .iter()
+                    .map(|e| (e.language.to_string(), e.bytes))
+                    .collect(),
+            },
+        );
+    }
+
+    /// Writes the results recorded with [`Cache::insert`] to disk.
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let file = CacheFile {
+            header: self.header.clone(),
+            entries: self.current.clone(),
+        };
+        let data = serde_json::to_vec(&file).map_err(std::io::Error::other)?;
+
+        // Write to a temporary file first so a concurrent reader never sees a
+        // partially written cache. Its name is unique, so that scans saving at
+        // the same time don't write over each other's; the last rename wins
+        let temporary = self.path.with_extension(format!(
+            "json.{}.{}.tmp",
+            std::process::id(),
+            TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&temporary, data)?;
+        fs::rename(&temporary, &self.path).inspect_err(|_| {
+            let _ = fs::remove_file(&temporary);
+        })?;
+        Ok(())
+    }
+}
+
+/// The static name of a language, if it still exists
+fn static_language(name: &str) -> Option<&'static str> {
+    definitions::LANGUAGES
+        .get_key_value(name)
+        .map(|(name, _)| name.as_str())
+}
+
+/// Modification time as (seconds, nanoseconds) since the Unix epoch
+fn modified(metadata: &Metadata) -> Option<(u64, u32)> {
+    let since_epoch = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((since_epoch.as_secs(), since_epoch.subsec_nanos()))
+}
@@ -1,6 +1,14 @@
 //! Command-line interface definitions using clap.
 
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use linguist::{HeuristicLimits, find_language};
+use linguist_types::{Language, LanguageType};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// FILE argument standing for content read from stdin
+pub const STDIN_FILE: &str = "-";
 
 /// Programming language detection tool based on GitHub Linguist
 #[derive(Parser, Debug)]
@@ -10,13 +18,49 @@ use clap::Parser;
     about = "Detect programming languages in files",
     long_about = "A fast programming language detection tool based on GitHub Linguist.\n\
                   Detects languages by file extension, exact filename, and content analysis
-                  as well as whether they are vendored."
+                  as well as whether they are vendored.",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
 pub struct Cli {
-    /// Files to analyze
-    #[arg(required = true, value_name = "FILE")]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Files to analyze ("-" reads content from stdin, see --name)
+    #[arg(
+        required_unless_present_any = ["stdio", "stdin", "files_from"],
+        value_name = "FILE"
+    )]
     pub files: Vec<String>,
 
+    /// Answer newline-delimited JSON detection requests from stdin until it closes
+    #[arg(long = "stdio", conflicts_with_all = ["files", "stdin", "files_from"])]
+    pub stdio: bool,
+
+    /// Read the content to analyze from stdin (same as a single "-" FILE)
+    #[arg(long = "stdin", conflicts_with_all = ["files", "files_from"], requires = "name")]
+    pub stdin: bool,
+
+    /// Path the content read from stdin is detected as, e.g. src/foo.h
+    #[arg(long = "name", value_name = "PATH")]
+    pub name: Option<String>,
+
+    /// Also analyze the files listed in LIST, one path per line ("-" reads the list from stdin)
+    #[arg(long = "files-from", value_name = "LIST")]
+    pub files_from: Option<String>,
+
+    /// Paths in the --files-from list are separated by NUL bytes instead of newlines
+    #[arg(short = '0', long = "null", requires = "files_from")]
+    pub null: bool,
+
+    /// Also print why each file is or isn't vendored
+    #[arg(long = "explain", conflicts_with = "stdio")]
+    pub explain: bool,
+
+    /// Number of --stdio requests processed concurrently (defaults to the number of CPUs)
+    #[arg(short = 'j', long = "jobs", value_name = "N")]
+    pub jobs: Option<usize>,
+
     /// Detect by file extension only
     #[arg(short = 'e', long = "by-extension")]
     pub by_extension: bool,
@@ -32,14 +76,87 @@ pub struct Cli {
     /// Use all detection methods (extension, filename, and content)
     #[arg(short = 'a', long = "all")]
     pub all: bool,
+
+    /// Maximum number of bytes read from each file for content analysis
+    #[arg(
+        long = "read-limit",
+        value_name = "BYTES",
+        global = true,
+        default_value_t = linguist::DEFAULT_READ_LIMIT
+    )]
+    pub read_limit: usize,
+
+    /// Maximum number of times each heuristic pattern may backtrack on a file
+    #[arg(
+        long = "backtrack-limit",
+        value_name = "N",
+        global = true,
+        default_value_t = linguist::DEFAULT_BACKTRACK_LIMIT
+    )]
+    pub backtrack_limit: usize,
+
+    /// Maximum number of bytes of a file's content heuristics examine
+    #[arg(
+        long = "heuristic-content-limit",
+        value_name = "BYTES",
+        global = true,
+        default_value_t = linguist::DEFAULT_READ_LIMIT
+    )]
+    pub heuristic_content_limit: usize,
+
+    /// Milliseconds after which heuristics stop trying further rules on a file
+    #[arg(long = "heuristic-time-limit", value_name = "MS", global = true)]
+    pub heuristic_time_limit: Option<u64>,
+
+    /// Output format (defaults to the configuration file's, or text)
+    #[arg(long = "format", value_enum, global = true)]
+    pub format: Option<Format>,
+
+    /// Read settings from FILE instead of the .linguist.toml found above the scanned path
+    #[arg(long = "config", value_name = "FILE", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Ignore any .linguist.toml file
+    #[arg(long = "no-config", global = true, conflicts_with = "config")]
+    pub no_config: bool,
+}
+
+/// How results are printed.
+#[derive(ValueEnum, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// Human-readable text
+    Text,
+
+    /// JSON, using the same schema as the `serve` API
+    Json,
 }
 
 impl Cli {
+    /// The files to analyze, where "-" stands for stdin.
+    pub fn inputs(&self) -> Vec<&str> {
+        if self.stdin {
+            vec![STDIN_FILE]
+        } else {
+            self.files.iter().map(String::as_str).collect()
+        }
+    }
+
+    /// Limits on the heuristics run on each file.
+    pub fn heuristic_limits(&self) -> HeuristicLimits {
+        HeuristicLimits {
+            backtrack_limit: self.backtrack_limit,
+            max_content_len: self.heuristic_content_limit,
+            time_budget: self.heuristic_time_limit.map(Duration::from_millis),
+        }
+    }
+
     /// Determines which detection methods should be used.
     ///
-    /// If no specific method is selected, defaults to all methods.
+    /// If no specific method is selected, defaults to the configured methods,
+    /// or all methods without a configuration.
     /// If --all is specified, it overrides individual selections.
-    pub fn detection_methods(&self) -> DetectionMethods {
+    pub fn detection_methods(&self, configured: Option<DetectionMethods>) -> DetectionMethods {
         // If --all is explicitly specified, use all methods
         if self.all {
             return DetectionMethods {
@@ -58,15 +175,260 @@ impl Cli {
             };
         }
 
-        // Default: use all methods if none specified
-        DetectionMethods {
+        // Default: use the configured methods, or all if none are configured
+        configured.unwrap_or(DetectionMethods {
             by_extension: true,
             by_filename: true,
             by_content: true,
+        })
+    }
+}
+
+/// Subcommands operating on whole directory trees.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Show the language breakdown of a directory
+    Stats(StatsArgs),
+
+    /// Compare the language breakdowns of two directories or git revisions
+    Diff(DiffArgs),
+
+    /// Run a local HTTP server exposing detection as a JSON API
+    Serve(ServeArgs),
+
+    /// List the files of a directory detected as one of the given languages
+    Ls(LsArgs),
+
+    /// Check a directory against a language policy, failing if it is broken
+    Check(CheckArgs),
+
+    /// List known languages, optionally filtered
+    Languages(LanguagesArgs),
+
+    /// Show the full definition of a language
+    Info(InfoArgs),
+}
+
+/// Arguments of the `stats` subcommand.
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// Directory, or zip or tar archive (optionally gzip or xz compressed), to scan
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub path: String,
+
+    /// Number of threads used to classify files (defaults to the number of CPUs)
+    #[arg(short = 'j', long = "jobs", value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Cache detection results in .git/linguist-cache so unchanged files aren't re-detected
+    #[arg(long = "cache")]
+    pub cache: bool,
+
+    /// Cache detection results in the given directory (implies --cache)
+    #[arg(long = "cache-dir", value_name = "DIR")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Analyze a git revision (commit, branch, tag or tree) of the repository at PATH
+    /// straight from its object database, without checking it out
+    #[arg(long = "rev", value_name = "COMMIT", conflicts_with_all = ["cache", "cache_dir"])]
+    pub rev: Option<String>,
+
+    /// Also count code, comment and blank lines per language (reads files in full)
+    #[arg(short = 'l', long = "lines")]
+    pub lines: bool,
+
+    /// Attribute embedded code (notebook cells, script and style blocks, Markdown
+    /// code fences) to its own language
+    #[arg(long = "embedded")]
+    pub embedded: bool,
+
+    /// Add up the size of the vendored and documentation directories skipped by the scan, such
+    /// as node_modules/ or docs/
+    #[arg(long = "count-pruned", conflicts_with = "rev")]
+    pub count_pruned: bool,
+}
+
+/// Arguments of the `diff` subcommand.
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Directory (or with --repo, git revision) before the change
+    #[arg(value_name = "A")]
+    pub before: String,
+
+    /// Directory (or with --repo, git revision) after the change
+    #[arg(value_name = "B")]
+    pub after: String,
+
+    /// Treat A and B as revisions of the git repository at this path
+    #[arg(long = "repo", value_name = "PATH")]
+    pub repo: Option<String>,
+
+    /// Number of threads used to classify files (defaults to the number of CPUs)
+    #[arg(short = 'j', long = "jobs", value_name = "N")]
+    pub jobs: Option<usize>,
+}
+
+/// Arguments of the `serve` subcommand.
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// Address to listen on (use port 0 to pick a free port)
+    #[arg(long = "bind", value_name = "ADDR", default_value = "127.0.0.1:8080")]
+    pub bind: String,
+
+    /// Number of requests handled concurrently (defaults to the number of CPUs)
+    #[arg(short = 'j', long = "jobs", value_name = "N")]
+    pub jobs: Option<usize>,
+}
+
+/// Arguments of the `ls` subcommand.
+#[derive(Args, Debug)]
+pub struct LsArgs {
+    /// Directory, or zip or tar archive, to list
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub path: String,
+
+    /// Language (name or alias) whose files are listed, including the languages in
+    /// its group; repeat or separate with commas for several languages
+    #[arg(
+        short = 'l',
+        long = "language",
+        value_name = "LANGUAGE",
+        value_delimiter = ',',
+        required = true
+    )]
+    pub languages: Vec<String>,
+
+    /// Also list vendored files
+    #[arg(long = "include-vendored")]
+    pub include_vendored: bool,
+
+    /// Also list generated files, such as lock files or code marked DO NOT EDIT
+    #[arg(long = "include-generated")]
+    pub include_generated: bool,
+
+    /// Separate paths with NUL bytes instead of newlines, e.g. for xargs -0
+    #[arg(short = '0', long = "null")]
+    pub null: bool,
+
+    /// Number of threads used to classify files (defaults to the number of CPUs)
+    #[arg(short = 'j', long = "jobs", value_name = "N")]
+    pub jobs: Option<usize>,
+}
+
+/// Arguments of the `check` subcommand.
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+    /// Directory, or zip or tar archive, to check
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub path: String,
+
+    /// Policy file (defaults to .linguist-policy.yml in PATH)
+    #[arg(long = "policy", value_name = "FILE")]
+    pub policy: Option<PathBuf>,
+
+    /// Check a git revision of the repository at PATH instead of its working tree
+    #[arg(long = "rev", value_name = "COMMIT")]
+    pub rev: Option<String>,
+
+    /// Number of threads used to classify files (defaults to the number of CPUs)
+    #[arg(short = 'j', long = "jobs", value_name = "N")]
+    pub jobs: Option<usize>,
+}
+
+/// Arguments of the `languages` subcommand.
+///
+/// Filters combine, so only languages matching all of them are listed.
+#[derive(Args, Debug)]
+pub struct LanguagesArgs {
+    /// Only list languages of this type
+    #[arg(long = "type", value_enum, value_name = "TYPE")]
+    pub language_type: Option<TypeFilter>,
+
+    /// Only list languages in this group, given by name or alias (e.g. TypeScript lists TSX)
+    #[arg(long = "group", value_name = "NAME")]
+    pub group: Option<String>,
+
+    /// Only list languages using this file extension, e.g. .m
+    #[arg(long = "extension", value_name = "EXT")]
+    pub extension: Option<String>,
+
+    /// Only list languages run by this interpreter, e.g. python3
+    #[arg(long = "interpreter", value_name = "PROGRAM")]
+    pub interpreter: Option<String>,
+}
+
+impl LanguagesArgs {
+    /// Whether a language passes every filter given on the command line.
+    pub fn matches(&self, name: &str, language: &Language) -> bool {
+        if let Some(language_type) = self.language_type
+            && language_type.as_language_type() != language.language_type
+        {
+            return false;
+        }
+
+        // Languages without a group are their own group
+        if let Some(ref group) = self.group {
+            let group = find_language(group).map_or(group.as_str(), |found| found.name);
+            if !language
+                .group
+                .as_deref()
+                .unwrap_or(name)
+                .eq_ignore_ascii_case(group)
+            {
+                return false;
+            }
+        }
+
+        if let Some(ref extension) = self.extension {
+            let extension = format!(".{}", extension.trim_start_matches('.'));
+            let extensions = language.extensions.as_deref().unwrap_or_default();
+            if !extensions
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(&extension))
+            {
+                return false;
+            }
+        }
+
+        if let Some(ref interpreter) = self.interpreter {
+            let interpreters = language.interpreters.as_deref().unwrap_or_default();
+            if !interpreters.contains(interpreter) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Language types accepted by `languages --type`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeFilter {
+    Data,
+    Programming,
+    Markup,
+    Prose,
+}
+
+impl TypeFilter {
+    fn as_language_type(self) -> LanguageType {
+        match self {
+            TypeFilter::Data => LanguageType::Data,
+            TypeFilter::Programming => LanguageType::Programming,
+            TypeFilter::Markup => LanguageType::Markup,
+            TypeFilter::Prose => LanguageType::Prose,
         }
     }
 }
 
+/// Arguments of the `info` subcommand.
+#[derive(Args, Debug)]
+pub struct InfoArgs {
+    /// Name or alias of the language, ignoring case
+    #[arg(value_name = "LANGUAGE")]
+    pub name: String,
+}
+
 /// Represents which detection methods should be used.
 #[derive(Debug, Clone, Copy)]
 pub struct DetectionMethods {
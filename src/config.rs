@@ -0,0 +1,150 @@
+//! Project configuration from a `.linguist.toml` file.
+//!
+//! The file is looked for in the scanned directory (the current directory
+//! when detecting single files) and then in each of its parents, so one file
+//! at the top of a repository covers every scan inside it:
+//!
+//! ```toml
+//! format = "json"
+//! methods = ["extension", "filename"]
+//! include = ["src/", "lib/"]
+//! exclude = ["*.min.js"]
+//! vendor = ["third_party/", "*.pb.go"]
+//!
+//! [extensions]
+//! ".inc" = "PHP"
+//! ```
+//!
+//! Globs follow gitattributes rules and anchored ones are relative to the
+//! scanned directory. `vendor` and `extensions` act like `linguist-vendored`
+//! and `linguist-language` lines of a `.gitattributes` file, so the tree's own
+//! `.gitattributes` files take precedence over them. Command-line flags take
+//! precedence over everything in the file.
+
+use crate::cli::{DetectionMethods, Format};
+use linguist::find_language;
+use linguist::gitattributes::GitAttributes;
+use linguist::repository::PathFilter;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the configuration file
+pub const CONFIG_FILENAME: &str = ".linguist.toml";
+
+/// Settings read from a configuration file, all optional.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Default output format
+    pub format: Option<Format>,
+
+    /// Default detection methods for single files
+    pub methods: Option<Vec<MethodName>>,
+
+    /// Only scan paths matching one of these globs
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    /// Never scan paths matching one of these globs
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// Treat paths matching one of these globs as vendored
+    #[serde(default)]
+    pub vendor: Vec<String>,
+
+    /// Languages (by name or alias) forced on files by their extension
+    #[serde(default)]
+    pub extensions: BTreeMap<String, String>,
+}
+
+/// A detection method, as named in `methods`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MethodName {
+    Extension,
+    Filename,
+    Content,
+}
+
+impl Config {
+    /// Finds the configuration file for a scan of `start`, looking in `start`
+    /// and then its parents.
+    pub fn discover(start: &Path) -> Option<PathBuf> {
+        let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+        start
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILENAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Reads and validates a configuration file.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let config: Config = toml::from_str(&fs::read_to_string(path)?)?;
+
+        // Catch mistakes up front rather than when the first scan needs them
+        config.filter()?;
+        config.overrides()?;
+        Ok(config)
+    }
+
+    /// The detection methods to use when no method flag is given.
+    pub fn detection_methods(&self) -> Option<DetectionMethods> {
+        self.methods.as_ref().map(|methods| DetectionMethods {
+            by_extension: methods.contains(&MethodName::Extension),
+            by_filename: methods.contains(&MethodName::Filename),
+            by_content: methods.contains(&MethodName::Content),
+        })
+    }
+
+    /// The include and exclude globs of a scan.
+    pub fn filter(&self) -> Result<PathFilter, Box<dyn Error>> {
+        Ok(PathFilter::new(&self.include, &self.exclude)?)
+    }
+
+    /// The vendor globs and extension overrides, as `.gitattributes` rules.
+    pub fn overrides(&self) -> Result<GitAttributes, Box<dyn Error>> {
+        let mut lines = Vec::new();
+        for pattern in &self.vendor {
+            if pattern.contains(char::is_whitespace) {
+                return Err(format!("Invalid vendor pattern '{pattern}'").into());
+            }
+            // Checks the glob, which gitattributes would silently ignore
+            PathFilter::new(&[pattern], &[])?;
+
+            // Directory patterns never match files in gitattributes, so they
+            // are spelled out as the files below the directory
+            let pattern = match pattern.strip_suffix('/') {
+                Some(dir) if dir.contains('/') => format!("{dir}/**"),
+                Some(dir) => format!("**/{dir}/**"),
+                None => pattern.clone(),
+            };
+            lines.push(format!("{pattern} linguist-vendored"));
+        }
+        for (extension, name) in &self.extensions {
+            let extension = extension.trim_start_matches('.');
+            if extension.is_empty()
+                || extension.contains(|c: char| c == '/' || c == '*' || c.is_whitespace())
+            {
+                return Err(format!("Invalid extension '{extension}'").into());
+            }
+            let language = find_language(name)
+                .ok_or_else(|| format!("Unknown language '{name}' for .{extension}"))?;
+            lines.push(format!(
+                "*.{extension} linguist-language={}",
+                // Attributes can't contain spaces, unlike some names
+                language
+                    .name
+                    .to_lowercase()
+                    .replace(char::is_whitespace, "-")
+            ));
+        }
+
+        let mut attributes = GitAttributes::new();
+        attributes.add_file("", &lines.join("\n"));
+        Ok(attributes)
+    }
+}
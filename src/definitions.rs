@@ -1,4 +1,6 @@
-use linguist_types::{Heuristics, Languages, VendorPatterns};
+use linguist_types::{
+    CommentSyntaxes, DocumentationPatterns, Heuristics, Languages, VendorPatterns,
+};
 use once_cell::sync::Lazy;
 
 // Load up static references to the linguist data generated by build.rs
@@ -8,6 +10,12 @@ use once_cell::sync::Lazy;
 const LANGUAGES_DATA: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/languages.json"));
 const HEURISTICS_DATA: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/heuristics.json"));
 const VENDOR_DATA: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/vendor.json"));
+const DOCUMENTATION_DATA: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/documentation.json"));
+const COMMENTS_DATA: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/comments.json"));
+
+/// Fingerprint of the embedded definitions, changing whenever the data does.
+/// Useful for invalidating anything derived from detection results.
+pub const DATA_VERSION: &str = env!("LINGUIST_DATA_VERSION");
 
 pub static LANGUAGES: Lazy<Languages> = Lazy::new(|| {
     serde_json::from_slice(LANGUAGES_DATA)
@@ -23,3 +31,13 @@ pub static VENDOR: Lazy<VendorPatterns> = Lazy::new(|| {
     serde_json::from_slice(VENDOR_DATA)
         .expect("Failed to deserialize vendor.json - this is a bug in the build process")
 });
+
+pub static DOCUMENTATION: Lazy<DocumentationPatterns> = Lazy::new(|| {
+    serde_json::from_slice(DOCUMENTATION_DATA)
+        .expect("Failed to deserialize documentation.json - this is a bug in the build process")
+});
+
+pub static COMMENTS: Lazy<CommentSyntaxes> = Lazy::new(|| {
+    serde_json::from_slice(COMMENTS_DATA)
+        .expect("Failed to deserialize comments.json - this is a bug in the build process")
+});
@@ -0,0 +1,146 @@
+//! Language breakdown differences between two directory trees or git revisions.
+
+use crate::git::analyze_revision;
+use crate::repository::{Breakdown, ScanOptions, analyze};
+use crate::{LinguistError, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Change in size and file count of a single language.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LanguageDelta {
+    /// Change in total bytes (negative when code was removed)
+    pub bytes: i64,
+
+    /// Change in number of files
+    pub files: i64,
+}
+
+/// A file present on both sides whose detected language differs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageChange {
+    /// Path of the file relative to the root
+    pub path: PathBuf,
+
+    /// Language detected before the change
+    pub before: Option<&'static str>,
+
+    /// Language detected after the change
+    pub after: Option<&'static str>,
+}
+
+/// The difference between two language breakdowns.
+#[derive(Debug, Clone, Default)]
+pub struct BreakdownDiff {
+    /// Per-language deltas. Languages whose totals didn't change are omitted.
+    pub languages: BTreeMap<&'static str, LanguageDelta>,
+
+    /// Files whose detected language changed, in path order
+    pub changed_files: Vec<LanguageChange>,
+
+    /// Files of the first breakdown that could not be classified, and so
+    /// don't count towards its totals
+    pub before_errors: Vec<(PathBuf, LinguistError)>,
+
+    /// Files of the second breakdown that could not be classified
+    pub after_errors: Vec<(PathBuf, LinguistError)>,
+}
+
+impl BreakdownDiff {
+    /// Language deltas sorted by the size of the byte change, largest first
+    /// (ties broken by name).
+    pub fn sorted_languages(&self) -> Vec<(&'static str, LanguageDelta)> {
+        let mut languages: Vec<_> = self
+            .languages
+            .iter()
+            .map(|(name, delta)| (*name, *delta))
+            .collect();
+        languages.sort_by(|a, b| {
+            b.1.bytes
+                .unsigned_abs()
+                .cmp(&a.1.bytes.unsigned_abs())
+                .then(a.0.cmp(b.0))
+        });
+        languages
+    }
+}
+
+/// Compares two breakdowns.
+///
+/// # Examples
+///
+/// ```no_run
+/// use linguist::diff::diff;
+/// use linguist::repository::{ScanOptions, analyze};
+///
+/// let options = ScanOptions::default();
+/// let before = analyze("old", &options)?;
+/// let after = analyze("new", &options)?;
+/// for (language, delta) in diff(&before, &after).sorted_languages() {
+///     println!("{language}: {:+} bytes", delta.bytes);
+/// }
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn diff(before: &Breakdown, after: &Breakdown) -> BreakdownDiff {
+    let mut languages: BTreeMap<&'static str, LanguageDelta> = BTreeMap::new();
+    for (language, stats) in &before.languages {
+        let delta = languages.entry(language).or_default();
+        delta.bytes -= stats.bytes as i64;
+        delta.files -= stats.files as i64;
+    }
+    for (language, stats) in &after.languages {
+        let delta = languages.entry(language).or_default();
+        delta.bytes += stats.bytes as i64;
+        delta.files += stats.files as i64;
+    }
+    languages.retain(|_, delta| *delta != LanguageDelta::default());
+
+    let before_languages: HashMap<&Path, Option<&'static str>> = before
+        .files
+        .iter()
+        .map(|file| (file.path.as_path(), file.language))
+        .collect();
+    let changed_files = after
+        .files
+        .iter()
+        .filter_map(|file| {
+            let previous = *before_languages.get(file.path.as_path())?;
+            (previous != file.language).then(|| LanguageChange {
+                path: file.path.clone(),
+                before: previous,
+                after: file.language,
+            })
+        })
+        .collect();
+
+    BreakdownDiff {
+        languages,
+        changed_files,
+        before_errors: before.errors.clone(),
+        after_errors: after.errors.clone(),
+    }
+}
+
+/// Compares the breakdowns of two directory trees.
+pub fn diff_directories<P: AsRef<Path>, Q: AsRef<Path>>(
+    before: P,
+    after: Q,
+    options: &ScanOptions,
+) -> Result<BreakdownDiff> {
+    Ok(diff(&analyze(before, options)?, &analyze(after, options)?))
+}
+
+/// Compares the breakdowns of two revisions of a git repository, without
+/// checking either out.
+pub fn diff_revisions<P: AsRef<Path>>(
+    repo: P,
+    before: &str,
+    after: &str,
+    options: &ScanOptions,
+) -> Result<BreakdownDiff> {
+    let repo = repo.as_ref();
+    Ok(diff(
+        &analyze_revision(repo, before, options)?,
+        &analyze_revision(repo, after, options)?,
+    ))
+}
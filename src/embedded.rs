@@ -0,0 +1,298 @@
+//! Embedded-language regions inside container files.
+//!
+//! HTML, Vue and Svelte files are split on their `<script>` and `<style>`
+//! blocks (and Vue `<template lang="...">` blocks), whose language comes from
+//! the `lang` or `type` attribute and otherwise defaults to JavaScript or CSS.
+//! Markdown files are split on fenced code blocks, whose language comes from
+//! the first word of the fence's info string. Names are resolved through the
+//! language names and aliases of languages.yml, and regions in unknown
+//! languages are left to the container.
+
+use crate::find_language;
+use std::ops::Range;
+
+/// Languages whose files can be split into regions
+const CONTAINERS: &[&str] = &["HTML", "Markdown", "Svelte", "Vue"];
+
+/// A part of a file written in another language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    /// Language of the region
+    pub language: &'static str,
+
+    /// Byte range of the region's content, excluding the surrounding tags or fences
+    pub range: Range<usize>,
+}
+
+/// Whether files in `language` can contain regions in other languages.
+pub fn has_regions(language: &str) -> bool {
+    CONTAINERS.contains(&language)
+}
+
+/// Finds the regions of `content`, a file in `language`, written in other
+/// languages.
+///
+/// Regions are returned in the order they appear and never overlap. Regions
+/// in the container's own language (e.g. a `markdown` fence in a Markdown
+/// file) are omitted, as are empty ones.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::embedded::find_regions;
+///
+/// let content = "<template><p>{{ msg }}</p></template>\n<script lang=\"ts\">\nlet msg = 1\n</script>\n";
+/// let regions = find_regions("Vue", content);
+/// assert_eq!(regions.len(), 1);
+/// assert_eq!(regions[0].language, "TypeScript");
+/// assert_eq!(&content[regions[0].range.clone()], "\nlet msg = 1\n");
+/// ```
+pub fn find_regions(language: &str, content: &str) -> Vec<Region> {
+    let regions = match language {
+        "HTML" | "Svelte" | "Vue" => markup_regions(language, content),
+        "Markdown" => fenced_regions(content),
+        _ => Vec::new(),
+    };
+
+    regions
+        .into_iter()
+        .filter(|region| region.language != language && !region.range.is_empty())
+        .collect()
+}
+
+/// Finds `<script>`, `<style>` and (in Vue) `<template>` blocks
+fn markup_regions(container: &str, content: &str) -> Vec<Region> {
+    // ASCII lowercasing keeps byte offsets, so positions found in `lower`
+    // apply to `content` too
+    let lower = content.to_ascii_lowercase();
+    let mut regions = Vec::new();
+    let mut pos = 0;
+
+    while let Some(offset) = lower[pos..].find('<') {
+        let start = pos + offset;
+        let rest = &lower[start + 1..];
+
+        if rest.starts_with("!--") {
+            pos = lower[start..]
+                .find("-->")
+                .map_or(lower.len(), |end| start + end + 3);
+            continue;
+        }
+
+        let tag = ["script", "style", "template"].into_iter().find(|tag| {
+            rest.strip_prefix(tag)
+                .and_then(|after| after.chars().next())
+                .is_some_and(|c| c.is_ascii_whitespace() || c == '>' || c == '/')
+        });
+        let Some(tag) = tag else {
+            pos = start + 1;
+            continue;
+        };
+
+        let attributes_start = start + 1 + tag.len();
+        let Some(tag_end) = start_tag_end(content, attributes_start) else {
+            break;
+        };
+        let attributes = &content[attributes_start..tag_end];
+        pos = tag_end + 1;
+
+        // Self-closing tags, e.g. `<script src="app.js" />`, have no content
+        if attributes.trim_end().ends_with('/') {
+            continue;
+        }
+
+        let close = format!("</{tag}");
+        let content_end = lower[pos..]
+            .find(&close)
+            .map_or(lower.len(), |end| pos + end);
+        let language = match tag {
+            "script" => block_language(attributes, "JavaScript"),
+            "style" => block_language(attributes, "CSS"),
+            // Templates are in the container's language unless they say otherwise
+            _ if container == "Vue" => block_language(attributes, container),
+            _ => None,
+        };
+
+        match language {
+            Some(language) => {
+                regions.push(Region {
+                    language,
+                    range: pos..content_end,
+                });
+                pos = content_end;
+            }
+            // HTML templates can hold scripts and styles of their own
+            None if tag == "template" => {}
+            None => pos = content_end,
+        }
+    }
+
+    regions
+}
+
+/// Index of the `>` closing a start tag whose attributes begin at `start`,
+/// skipping any inside quoted attribute values
+fn start_tag_end(content: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in content[start..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(start + i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Language of a block from its `lang` or `type` attribute. `None` if the
+/// attribute names a language we don't know.
+fn block_language(attributes: &str, default: &str) -> Option<&'static str> {
+    let attributes = parse_attributes(attributes);
+    let value = |name: &str| {
+        attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim())
+    };
+
+    if let Some(lang) = value("lang") {
+        return find_language(lang).map(|lang| lang.name);
+    }
+    let name = match value("type") {
+        Some(media_type) => media_type_language(media_type)?,
+        None => default,
+    };
+    find_language(name).map(|lang| lang.name)
+}
+
+/// Maps a `type` attribute, such as `text/typescript` or `module`, to a
+/// language name
+fn media_type_language(media_type: &str) -> Option<&str> {
+    let essence = media_type.split(';').next().unwrap_or_default().trim();
+    if essence.is_empty() {
+        return Some("JavaScript");
+    }
+
+    match essence.to_ascii_lowercase().as_str() {
+        "module"
+        | "text/javascript"
+        | "application/javascript"
+        | "text/ecmascript"
+        | "application/ecmascript" => Some("JavaScript"),
+        "importmap" | "speculationrules" | "application/json" | "application/ld+json" => {
+            Some("JSON")
+        }
+        _ => {
+            let (_, subtype) = essence.split_once('/')?;
+            Some(subtype.strip_prefix("x-").unwrap_or(subtype))
+        }
+    }
+}
+
+/// Splits the attributes of a start tag into (name, value) pairs. Attributes
+/// without a value get an empty one.
+fn parse_attributes(attributes: &str) -> Vec<(&str, &str)> {
+    let mut parsed = Vec::new();
+    let mut rest = attributes;
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+        if rest.is_empty() {
+            break;
+        }
+
+        let name_end = rest
+            .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let name = &rest[..name_end];
+        rest = rest[name_end..].trim_start();
+
+        let Some(after_equals) = rest.strip_prefix('=') else {
+            parsed.push((name, ""));
+            continue;
+        };
+        let after_equals = after_equals.trim_start();
+        let (value, remaining) = match after_equals.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let quoted = &after_equals[1..];
+                let end = quoted.find(quote).unwrap_or(quoted.len());
+                (&quoted[..end], quoted.get(end + 1..).unwrap_or_default())
+            }
+            _ => {
+                let end = after_equals
+                    .find(|c: char| c.is_ascii_whitespace())
+                    .unwrap_or(after_equals.len());
+                after_equals.split_at(end)
+            }
+        };
+        parsed.push((name, value));
+        rest = remaining;
+    }
+
+    parsed
+}
+
+/// Finds fenced code blocks whose info string names a known language
+fn fenced_regions(content: &str) -> Vec<Region> {
+    let mut regions = Vec::new();
+
+    // The open fence's character, length, language and content start
+    let mut open: Option<(char, usize, Option<&'static str>, usize)> = None;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let text = line.trim();
+        let fence_char = text.chars().next().filter(|c| *c == '`' || *c == '~');
+        let fence_len = fence_char.map_or(0, |c| text.len() - text.trim_start_matches(c).len());
+
+        match open {
+            None if indent <= 3 && fence_len >= 3 => {
+                let fence_char = fence_char.unwrap_or_default();
+                let info = text[fence_len..].trim();
+
+                // Backtick fences can't have backticks in their info string
+                if fence_char == '`' && info.contains('`') {
+                    continue;
+                }
+                let name = info
+                    .split(|c: char| c.is_whitespace() || c == ',')
+                    .next()
+                    .unwrap_or_default()
+                    .trim_matches(|c| c == '{' || c == '}')
+                    .trim_start_matches('.');
+                let language = find_language(name).map(|lang| lang.name);
+                open = Some((fence_char, fence_len, language, offset));
+            }
+            Some((open_char, open_len, language, start))
+                if indent <= 3
+                    && fence_char == Some(open_char)
+                    && fence_len >= open_len
+                    && text[fence_len..].trim().is_empty() =>
+            {
+                if let Some(language) = language {
+                    regions.push(Region {
+                        language,
+                        range: start..line_start,
+                    });
+                }
+                open = None;
+            }
+            _ => {}
+        }
+    }
+
+    // An unclosed fence runs to the end of the document
+    if let Some((_, _, Some(language), start)) = open {
+        regions.push(Region {
+            language,
+            range: start..content.len(),
+        });
+    }
+
+    regions
+}
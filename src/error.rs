@@ -1,4 +1,5 @@
 use std::fmt;
+use std::time::Duration;
 
 /// Errors that can occur during language detection
 #[derive(Debug, Clone, PartialEq)]
@@ -9,8 +10,33 @@ pub enum LinguistError {
     /// A regex pattern in the heuristics is malformed
     InvalidRegex { pattern: String, error: String },
 
+    /// A regex pattern gave up on some content, e.g. by exceeding its
+    /// backtrack limit
+    RegexLimitExceeded { pattern: String, error: String },
+
+    /// Heuristics ran out of time on some content before trying every rule
+    TimeBudgetExceeded(Duration),
+
     /// A named pattern referenced in heuristics doesn't exist
     MissingNamedPattern(String),
+
+    /// Reading file content failed
+    Io(String),
+
+    /// A git command failed, e.g. because the revision doesn't exist
+    Git(String),
+
+    /// Content expected to be a Jupyter notebook couldn't be parsed as one
+    InvalidNotebook(String),
+
+    /// An archive couldn't be read, e.g. because it is truncated or corrupt
+    InvalidArchive(String),
+
+    /// A language policy file is malformed or names unknown languages
+    InvalidPolicy(String),
+
+    /// A glob selecting paths is malformed
+    InvalidGlob { glob: String, error: String },
 }
 
 impl fmt::Display for LinguistError {
@@ -22,11 +48,41 @@ impl fmt::Display for LinguistError {
             LinguistError::InvalidRegex { pattern, error } => {
                 write!(f, "Invalid regex pattern '{pattern}': {error}")
             }
+            LinguistError::RegexLimitExceeded { pattern, error } => {
+                write!(f, "Regex pattern '{pattern}' gave up: {error}")
+            }
+            LinguistError::TimeBudgetExceeded(budget) => {
+                write!(f, "Heuristics gave up after {budget:?}")
+            }
             LinguistError::MissingNamedPattern(name) => {
                 write!(f, "Named pattern '{name}' not found in heuristics")
             }
+            LinguistError::Io(error) => {
+                write!(f, "I/O error: {error}")
+            }
+            LinguistError::Git(error) => {
+                write!(f, "Git error: {error}")
+            }
+            LinguistError::InvalidNotebook(error) => {
+                write!(f, "Invalid notebook: {error}")
+            }
+            LinguistError::InvalidArchive(error) => {
+                write!(f, "Invalid archive: {error}")
+            }
+            LinguistError::InvalidPolicy(error) => {
+                write!(f, "Invalid policy: {error}")
+            }
+            LinguistError::InvalidGlob { glob, error } => {
+                write!(f, "Invalid glob '{glob}': {error}")
+            }
         }
     }
 }
 
 impl std::error::Error for LinguistError {}
+
+impl From<std::io::Error> for LinguistError {
+    fn from(error: std::io::Error) -> Self {
+        LinguistError::Io(error.to_string())
+    }
+}
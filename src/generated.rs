@@ -0,0 +1,124 @@
+//! Generated files and binary content, both left out of language statistics.
+//!
+//! A file is generated if its name marks it as the output of a known tool
+//! (lock files, protocol buffer stubs, source maps...) or if one of its first
+//! lines carries a marker such as `Code generated ... DO NOT EDIT.` or
+//! `@generated`. Content is binary if its start has a NUL byte or too many
+//! control characters, as git decides. Both checks only need a prefix of the
+//! content, so callers can pass the bytes they already read for detection.
+
+use std::path::Path;
+
+/// Number of leading lines searched for a generated-code marker
+const MARKER_LINES: usize = 10;
+
+/// Number of leading bytes of a file worth passing to [`is_generated`], enough
+/// for the lines searched for a marker
+pub const MARKER_LEN: usize = 4096;
+
+/// Number of leading bytes looked at to decide whether content is binary,
+/// the same as git
+const BINARY_CHECK_LEN: usize = 8000;
+
+/// Names of files written by package managers
+const GENERATED_FILENAMES: &[&str] = &[
+    "Cargo.lock",
+    "Gemfile.lock",
+    "Package.resolved",
+    "Pipfile.lock",
+    "Podfile.lock",
+    "composer.lock",
+    "flake.lock",
+    "go.sum",
+    "mix.lock",
+    "npm-shrinkwrap.json",
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "pubspec.lock",
+    "yarn.lock",
+];
+
+/// File name suffixes of code and maps written by compilers
+const GENERATED_SUFFIXES: &[&str] = &[
+    ".Designer.cs",
+    ".css.map",
+    ".designer.cs",
+    ".freezed.dart",
+    ".g.dart",
+    ".js.map",
+    ".pb.cc",
+    ".pb.go",
+    ".pb.h",
+    ".pb.swift",
+    "_pb.js",
+    "_pb2.py",
+    "_pb2_grpc.py",
+];
+
+/// Markers that generators put near the top of the files they write
+const MARKERS: &[&str] = &[
+    "<auto-generated",
+    "@generated",
+    "Code generated by",
+    "DO NOT EDIT",
+    "Generated by the protocol buffer compiler",
+];
+
+/// Whether a file is generated, judging by its path and a prefix of its
+/// content.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::generated::is_generated;
+///
+/// assert!(is_generated("Cargo.lock", b""));
+/// assert!(is_generated("api/v1/user.pb.go", b""));
+/// assert!(is_generated("src/schema.rs", b"// @generated by diesel\n"));
+/// assert!(!is_generated("src/main.rs", b"fn main() {}\n"));
+/// ```
+pub fn is_generated<P: AsRef<Path>>(path: P, content: &[u8]) -> bool {
+    let Some(name) = path.as_ref().file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    if GENERATED_FILENAMES.contains(&name)
+        || GENERATED_SUFFIXES
+            .iter()
+            .any(|suffix| name.len() > suffix.len() && name.ends_with(suffix))
+    {
+        return true;
+    }
+
+    content
+        .split(|&byte| byte == b'\n')
+        .take(MARKER_LINES)
+        .map(String::from_utf8_lossy)
+        .any(|line| MARKERS.iter().any(|marker| line.contains(marker)))
+}
+
+/// Whether content is binary rather than text, judging by its first 8000
+/// bytes: it is if they have a NUL byte, or if more than one in ten is a
+/// control character other than whitespace and escape.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::generated::is_binary;
+///
+/// assert!(is_binary(b"\x7fELF\x02\x01\x01\x00"));
+/// assert!(!is_binary("fn main() {}\n// caf\u{e9}\n".as_bytes()));
+/// ```
+pub fn is_binary(content: &[u8]) -> bool {
+    let content = &content[..content.len().min(BINARY_CHECK_LEN)];
+    if content.contains(&0) {
+        return true;
+    }
+    let control = content
+        .iter()
+        .filter(|&&byte| {
+            (byte < 0x20 && !matches!(byte, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b)) || byte == 0x7f
+        })
+        .count();
+    control * 10 > content.len()
+}
@@ -0,0 +1,364 @@
+//! Analysis of git revisions straight from the object database.
+//!
+//! Files are listed with `git ls-tree` and their content is streamed from
+//! `git cat-file --batch`, so no working tree (or checkout) is needed and bare
+//! repositories work too. The results match a working-tree scan of the same
+//! revision, including `.gitattributes` overrides taken from that revision.
+
+use crate::lines::count_lines;
+use crate::repository::{
+    Breakdown, ScanOptions, classify_reader, collect_breakdown, find_embedded, may_embed,
+    parallel_map,
+};
+use crate::{LinguistError, Result};
+use std::io::{self, BufRead, BufReader, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
+
+/// Mode git uses for symbolic links, which aren't analyzed
+const SYMLINK_MODE: &str = "120000";
+
+/// A file (blob) in a git tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    /// Path of the file relative to the root of the tree
+    pub path: PathBuf,
+
+    /// Object id of the blob
+    pub oid: String,
+
+    /// Size of the blob in bytes
+    pub size: u64,
+}
+
+/// Lists every file in the tree of a revision, in path order.
+///
+/// `repo` may be a working copy or a bare repository, and `rev` anything git
+/// accepts as a tree-ish (a commit, branch, tag or tree id). Submodules and
+/// symbolic links are skipped. Paths are kept as git stores them, even if they
+/// aren't valid UTF-8, so that only those files fail to classify.
+pub fn list_tree<P: AsRef<Path>>(repo: P, rev: &str) -> Result<Vec<TreeEntry>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo.as_ref())
+        .args([
+            "ls-tree",
+            "-r",
+            "-l",
+            "-z",
+            "--full-tree",
+            "--end-of-options",
+            rev,
+        ])
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|e| LinguistError::Git(format!("failed to run git: {e}")))?;
+
+    if !output.status.success() {
+        return Err(LinguistError::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    // Each record is "<mode> <type> <oid> <padded size>\t<path>\0"
+    //
+    let mut entries = Vec::new();
+    for record in output.stdout.split(|byte| *byte == 0) {
+        if record.is_empty() {
+            continue;
+        }
+        let unexpected = || {
+            LinguistError::Git(format!(
+                "unexpected ls-tree output: {}",
+                String::from_utf8_lossy(record)
+            ))
+        };
+        let tab = record
+            .iter()
+            .position(|byte| *byte == b'\t')
+            .ok_or_else(unexpected)?;
+        let info = std::str::from_utf8(&record[..tab]).map_err(|_| unexpected())?;
+        let path = path_from_bytes(&record[tab + 1..]);
+
+        let fields: Vec<&str> = info.split_whitespace().collect();
+        let [mode, kind, oid, size] = fields[..] else {
+            return Err(unexpected());
+        };
+        if kind != "blob" || mode == SYMLINK_MODE {
+            continue;
+        }
+
+        entries.push(TreeEntry {
+            path,
+            oid: oid.to_string(),
+            size: size
+                .parse()
+                .map_err(|_| LinguistError::Git(format!("invalid blob size: {size}")))?,
+        });
+    }
+
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+/// Path from the raw bytes git stores for it
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+/// Path from the raw bytes git stores for it. Other platforms can't represent
+/// arbitrary bytes, so invalid UTF-8 is replaced.
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Builds the language breakdown of a revision without checking it out.
+///
+/// Produces the same result as [`crate::repository::analyze`] on a checkout of
+/// `rev`. The cache in [`ScanOptions::cache_dir`] isn't used, since blobs of a
+/// revision never change.
+///
+/// # Examples
+///
+/// ```no_run
+/// use linguist::git::analyze_revision;
+/// use linguist::repository::ScanOptions;
+///
+/// let breakdown = analyze_revision(".", "HEAD~10", &ScanOptions::default())?;
+/// println!("{} bytes of code", breakdown.total_bytes());
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn analyze_revision<P: AsRef<Path>>(
+    repo: P,
+    rev: &str,
+    options: &ScanOptions,
+) -> Result<Breakdown> {
+    let repo = repo.as_ref();
+    let mut entries = list_tree(repo, rev)?;
+    let store = BlobPool::spawn(repo)?;
+
+    // Overrides come from the revision's own .gitattributes, shallowest first
+    //
+    let mut attribute_files: Vec<&TreeEntry> = entries
+        .iter()
+        .filter(|entry| entry.path.file_name() == Some(".gitattributes".as_ref()))
+        .collect();
+    attribute_files.sort_by_key(|entry| entry.path.components().count());
+
+    let mut attributes = options.overrides.clone();
+    for entry in attribute_files {
+        let content = store.read_prefix(&entry.oid, entry.size as usize)?;
+        attributes.add_file(
+            entry.path.parent().unwrap_or(Path::new("")),
+            &String::from_utf8_lossy(&content),
+        );
+    }
+    entries.retain(|entry| options.filter.matches(&entry.path));
+
+    let results = parallel_map(&entries, options.jobs, |entry| {
+        let blob = LazyBlob {
+            store: &store,
+            oid: &entry.oid,
+            limit: options.read_limit,
+            content: None,
+        };
+        let mut file = classify_reader(&entry.path, entry.size, blob, options)?;
+        attributes.apply(&mut file);
+
+        if options.count_lines
+            && let Some(language) = file.language
+        {
+            file.lines = Some(store.read_with(&entry.oid, |blob| count_lines(blob, language))?);
+        }
+
+        if options.embedded_languages
+            && let Some(language) = file.language
+            && may_embed(language)
+        {
+            file.embedded = store.read_with(&entry.oid, |blob| find_embedded(language, blob))?;
+        }
+
+        Ok(file)
+    });
+
+    let paths: Vec<PathBuf> = entries.into_iter().map(|entry| entry.path).collect();
+    Ok(collect_breakdown(&paths, results))
+}
+
+/// `git cat-file --batch` processes shared by the workers of a scan. A worker
+/// takes one for the length of a request, so blobs are read, classified and
+/// counted in parallel, and another process is only spawned when all are busy.
+struct BlobPool {
+    repo: PathBuf,
+    idle: Mutex<Vec<BlobStore>>,
+}
+
+impl BlobPool {
+    /// Starts with a single process, so that failing to run git is reported
+    /// before any blob is requested
+    fn spawn(repo: &Path) -> Result<Self> {
+        Ok(BlobPool {
+            repo: repo.to_path_buf(),
+            idle: Mutex::new(vec![BlobStore::spawn(repo)?]),
+        })
+    }
+
+    /// Streams a blob's content through `f`, see [`BlobStore::read_with`]
+    fn read_with<T>(&self, oid: &str, f: impl FnOnce(&mut dyn BufRead) -> Result<T>) -> Result<T> {
+        let idle = self.idle.lock().expect("blob pool lock poisoned").pop();
+        let mut store = match idle {
+            Some(store) => store,
+            None => BlobStore::spawn(&self.repo)?,
+        };
+        let result = store.read_with(oid, f);
+        self.idle
+            .lock()
+            .expect("blob pool lock poisoned")
+            .push(store);
+        result
+    }
+
+    /// Reads at most `limit` bytes of a blob. The remainder still has to be
+    /// drained from the pipe, but is discarded without being buffered.
+    fn read_prefix(&self, oid: &str, limit: usize) -> Result<Vec<u8>> {
+        self.read_with(oid, |blob| {
+            let mut content = Vec::with_capacity(limit.min(64 * 1024));
+            blob.take(limit as u64).read_to_end(&mut content)?;
+            Ok(content)
+        })
+    }
+}
+
+/// A long running `git cat-file --batch` process serving blob contents
+struct BlobStore {
+    repo: PathBuf,
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl BlobStore {
+    fn spawn(repo: &Path) -> Result<Self> {
+        let mut child = Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| LinguistError::Git(format!("failed to run git: {e}")))?;
+
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take().map(BufReader::new);
+        match (stdin, stdout) {
+            (Some(stdin), Some(stdout)) => Ok(BlobStore {
+                repo: repo.to_path_buf(),
+                child,
+                stdin: Some(stdin),
+                stdout,
+            }),
+            _ => Err(LinguistError::Git(
+                "failed to open git cat-file pipes".into(),
+            )),
+        }
+    }
+
+    /// Streams a blob's content through `f`. Whatever `f` leaves unread is
+    /// drained from the pipe afterwards.
+    ///
+    /// If the pipe fails partway through a blob, it's out of sync with the
+    /// requests, so git is restarted for the next one.
+    fn read_with<T>(
+        &mut self,
+        oid: &str,
+        f: impl FnOnce(&mut dyn BufRead) -> Result<T>,
+    ) -> Result<T> {
+        match self.exchange(oid, f) {
+            Ok(result) => result,
+            Err(e) => {
+                // Git may be blocked writing the rest of the blob, so it
+                // wouldn't exit when its stdin is closed
+                let _ = self.child.kill();
+                let repo = self.repo.clone();
+                *self = BlobStore::spawn(&repo)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Requests a blob and streams it through `f`. The outer error means the
+    /// pipe is in an unknown state, the inner one is the result of `f`.
+    fn exchange<T>(
+        &mut self,
+        oid: &str,
+        f: impl FnOnce(&mut dyn BufRead) -> Result<T>,
+    ) -> Result<Result<T>> {
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| LinguistError::Git("git cat-file has exited".into()))?;
+        writeln!(stdin, "{oid}")?;
+        stdin.flush()?;
+
+        // Header is "<oid> blob <size>", or "<oid> missing" without content
+        //
+        let mut header = String::new();
+        self.stdout.read_line(&mut header)?;
+        let size: u64 = match header.split_whitespace().collect::<Vec<_>>()[..] {
+            [_, "blob", size] => size
+                .parse()
+                .map_err(|_| LinguistError::Git(format!("invalid blob size: {size}")))?,
+            [_, "missing"] => {
+                return Ok(Err(LinguistError::Git(format!("cannot read blob {oid}"))));
+            }
+            _ => return Err(LinguistError::Git(format!("cannot read blob {oid}"))),
+        };
+
+        let mut blob = (&mut self.stdout).take(size);
+        let result = f(&mut blob);
+
+        // Skip the rest of the blob and its trailing newline
+        io::copy(&mut blob, &mut io::sink())?;
+        let mut newline = [0];
+        self.stdout.read_exact(&mut newline)?;
+
+        Ok(result)
+    }
+}
+
+impl Drop for BlobStore {
+    fn drop(&mut self) {
+        // Closing stdin tells git to exit
+        drop(self.stdin.take());
+        let _ = self.child.wait();
+    }
+}
+
+/// Reader over a blob that is only fetched from the store on first read, so
+/// files detected by name alone never touch the object database
+struct LazyBlob<'a> {
+    store: &'a BlobPool,
+    oid: &'a str,
+    limit: usize,
+    content: Option<Cursor<Vec<u8>>>,
+}
+
+impl Read for LazyBlob<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.content.is_none() {
+            let content = self
+                .store
+                .read_prefix(self.oid, self.limit)
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            self.content = Some(Cursor::new(content));
+        }
+        self.content
+            .as_mut()
+            .map_or(Ok(0), |content| content.read(buf))
+    }
+}
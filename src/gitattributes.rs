@@ -0,0 +1,182 @@
+//! Linguist overrides from `.gitattributes` files.
+//!
+//! Supports the attributes upstream Linguist uses to correct its results:
+//!
+//! - `linguist-vendored` (or `-linguist-vendored`) to mark paths as vendored (or not)
+//! - `linguist-language=<name or alias>` to force the language of paths
+//!
+//! Patterns follow gitattributes rules: a pattern without a slash matches a
+//! file name at any depth below the `.gitattributes` file, otherwise it is
+//! matched against the path relative to that file's directory. Later lines,
+//! and files in deeper directories, take precedence.
+
+use crate::find_language;
+use crate::repository::FileEntry;
+use globset::{GlobBuilder, GlobMatcher};
+use std::path::Path;
+
+/// The linguist overrides collected from one or more `.gitattributes` files.
+#[derive(Debug, Clone)]
+pub struct GitAttributes {
+    rules: Vec<Rule>,
+
+    /// FNV-1a hash of every file added, to tell when the overrides change
+    fingerprint: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    /// Directory of the `.gitattributes` file, relative to the root ("" or "dir/")
+    base: String,
+    matcher: GlobMatcher,
+    vendored: Option<bool>,
+    language: Option<&'static str>,
+}
+
+impl Default for GitAttributes {
+    fn default() -> Self {
+        GitAttributes {
+            rules: Vec::new(),
+            fingerprint: 0xcbf29ce484222325,
+        }
+    }
+}
+
+impl GitAttributes {
+    /// Creates an empty set of overrides.
+    pub fn new() -> Self {
+        GitAttributes::default()
+    }
+
+    /// Adds the rules of a `.gitattributes` file.
+    ///
+    /// `dir` is the directory containing the file, relative to the repository
+    /// root. Files should be added from the root downwards so that deeper
+    /// files take precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use linguist::gitattributes::GitAttributes;
+    ///
+    /// let mut attributes = GitAttributes::new();
+    /// attributes.add_file("", "*.inc linguist-language=PHP\nthird_party/** linguist-vendored");
+    /// assert_eq!(attributes.language("lib/util.inc"), Some("PHP"));
+    /// assert_eq!(attributes.vendored("third_party/zlib/zlib.c"), Some(true));
+    /// assert_eq!(attributes.vendored("src/main.c"), None);
+    /// ```
+    pub fn add_file<P: AsRef<Path>>(&mut self, dir: P, content: &str) {
+        let mut base = dir.as_ref().to_string_lossy().replace('\\', "/");
+        if !base.is_empty() && !base.ends_with('/') {
+            base.push('/');
+        }
+
+        for byte in base.bytes().chain([0]).chain(content.bytes()).chain([0]) {
+            self.fingerprint ^= u64::from(byte);
+            self.fingerprint = self.fingerprint.wrapping_mul(0x100000001b3);
+        }
+
+        for line in content.lines() {
+            if let Some(rule) = parse_line(&base, line) {
+                self.rules.push(rule);
+            }
+        }
+    }
+
+    /// Whether `path` is marked vendored (`Some(true)`) or explicitly not
+    /// vendored (`Some(false)`). `None` if no rule sets the attribute.
+    pub fn vendored<P: AsRef<Path>>(&self, path: P) -> Option<bool> {
+        self.last_match(path.as_ref(), |rule| rule.vendored)
+    }
+
+    /// The language forced on `path` by `linguist-language`, if any.
+    pub fn language<P: AsRef<Path>>(&self, path: P) -> Option<&'static str> {
+        self.last_match(path.as_ref(), |rule| rule.language)
+    }
+
+    /// Applies any overrides for the entry's path to a classified file.
+    pub fn apply(&self, entry: &mut FileEntry) {
+        if let Some(vendored) = self.vendored(&entry.path) {
+            entry.vendored = vendored;
+        }
+        if let Some(language) = self.language(&entry.path) {
+            entry.language = Some(language);
+        }
+    }
+
+    /// Whether any rule marks paths as not vendored, which could apply to
+    /// files below directories otherwise vendored as a whole
+    pub(crate) fn unvendors_any(&self) -> bool {
+        self.rules.iter().any(|rule| rule.vendored == Some(false))
+    }
+
+    /// Changes whenever different `.gitattributes` content is added
+    pub(crate) fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    fn last_match<T>(&self, path: &Path, value: impl Fn(&Rule) -> Option<T>) -> Option<T> {
+        let path = path.to_string_lossy().replace('\\', "/");
+        self.rules.iter().rev().find_map(|rule| {
+            let relative = path.strip_prefix(&rule.base)?;
+            if rule.matcher.is_match(relative) {
+                value(rule)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Parses a single `.gitattributes` line, keeping it only if it sets a
+/// linguist attribute
+fn parse_line(base: &str, line: &str) -> Option<Rule> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let mut fields = line.split_whitespace();
+    let pattern = fields.next()?;
+
+    let mut vendored = None;
+    let mut language = None;
+    for attribute in fields {
+        match attribute {
+            "linguist-vendored" | "linguist-vendored=true" => vendored = Some(true),
+            "-linguist-vendored" | "linguist-vendored=false" => vendored = Some(false),
+            _ => {
+                if let Some(name) = attribute.strip_prefix("linguist-language=") {
+                    language = find_language(name).map(|lang| lang.name);
+                }
+            }
+        }
+    }
+    if vendored.is_none() && language.is_none() {
+        return None;
+    }
+
+    // Directory patterns never match files in gitattributes
+    if pattern.ends_with('/') {
+        return None;
+    }
+
+    // Patterns without a slash match at any depth, others are anchored
+    let glob = match pattern.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if pattern.contains('/') => pattern.to_string(),
+        None => format!("**/{pattern}"),
+    };
+    let matcher = GlobBuilder::new(&glob)
+        .literal_separator(true)
+        .build()
+        .ok()?
+        .compile_matcher();
+
+    Some(Rule {
+        base: base.to_string(),
+        matcher,
+        vendored,
+        language,
+    })
+}
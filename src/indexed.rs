@@ -1,7 +1,7 @@
 use crate::definitions;
+use crate::vendor::VendorMatcher;
 use linguist_types::Disambiguation;
 use once_cell::sync::Lazy;
-use fancy_regex::Regex;
 use std::collections::{BTreeSet, HashMap};
 
 //
@@ -11,6 +11,8 @@ use std::collections::{BTreeSet, HashMap};
 pub type Filename = String;
 pub type Extension = String;
 pub type LanguageName = String;
+pub type Alias = String;
+pub type Interpreter = String;
 
 pub static LANGUAGES_BY_FILENAME: Lazy<HashMap<Filename, BTreeSet<LanguageName>>> =
     Lazy::new(|| {
@@ -52,6 +54,79 @@ pub static LANGUAGES_BY_EXTENSION: Lazy<HashMap<Extension, BTreeSet<LanguageName
         index
     });
 
+pub static LANGUAGES_BY_LOWERCASE_EXTENSION: Lazy<HashMap<Extension, BTreeSet<LanguageName>>> =
+    Lazy::new(|| {
+        // Fold the extension index by lowercased extension, as the fallback for
+        // extensions whose case matches no entry of languages.yml
+        //
+        fold_case(&LANGUAGES_BY_EXTENSION)
+    });
+
+/// Merges the entries of an index whose keys differ only in case
+fn fold_case(
+    index: &HashMap<Extension, BTreeSet<LanguageName>>,
+) -> HashMap<Extension, BTreeSet<LanguageName>> {
+    let mut folded: HashMap<Extension, BTreeSet<LanguageName>> = HashMap::new();
+
+    for (extension, lang_names) in index {
+        folded
+            .entry(extension.to_lowercase())
+            .or_default()
+            .extend(lang_names.iter().cloned());
+    }
+
+    folded
+}
+
+pub static LANGUAGES_BY_INTERPRETER: Lazy<HashMap<Interpreter, BTreeSet<LanguageName>>> =
+    Lazy::new(|| {
+        // Process the LANGUAGES struct, building up the index
+        //
+        let mut index = HashMap::new();
+
+        for (lang_name, lang_def) in definitions::LANGUAGES.iter() {
+            if let Some(ref interpreters) = lang_def.interpreters {
+                for interpreter in interpreters {
+                    index
+                        .entry(interpreter.clone())
+                        .or_insert_with(BTreeSet::new)
+                        .insert(lang_name.clone());
+                }
+            }
+        }
+
+        index
+    });
+
+pub static LANGUAGES_BY_ALIAS: Lazy<HashMap<Alias, LanguageName>> = Lazy::new(|| {
+    // Every language is implicitly aliased by its lowercased name, and by that
+    // name with whitespace replaced by dashes (e.g. "common-lisp"), as upstream.
+    // Names are indexed before aliases so they always win a collision, and
+    // languages are visited in sorted order to keep the index deterministic
+    //
+    let mut index = HashMap::new();
+    let mut languages: Vec<_> = definitions::LANGUAGES.iter().collect();
+    languages.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (lang_name, _) in &languages {
+        let lowercased = lang_name.to_lowercase();
+        let dashed = lowercased.replace(char::is_whitespace, "-");
+        for alias in [lowercased, dashed] {
+            index.entry(alias).or_insert_with(|| (*lang_name).clone());
+        }
+    }
+
+    for (lang_name, lang_def) in &languages {
+        for alias in lang_def.aliases.iter().flatten() {
+            index
+                .entry(alias.to_lowercase())
+                .or_insert_with(|| (*lang_name).clone());
+        }
+    }
+
+    index
+});
+
 pub static DISAMBIGUATIONS_BY_EXTENSION: Lazy<HashMap<Extension, Vec<Disambiguation>>> =
     Lazy::new(|| {
         // Process the HEURISTICS struct, building up the index
@@ -70,20 +145,105 @@ pub static DISAMBIGUATIONS_BY_EXTENSION: Lazy<HashMap<Extension, Vec<Disambiguat
         index
     });
 
-pub static VENDOR_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+pub static DISAMBIGUATIONS_BY_LOWERCASE_EXTENSION: Lazy<HashMap<Extension, Vec<Disambiguation>>> =
+    Lazy::new(|| {
+        // Same fallback as for languages, for heuristics
+        //
+        let mut index: HashMap<Extension, Vec<Disambiguation>> = HashMap::new();
+
+        // Sorted so disambiguations sharing a lowercased extension keep a stable order
+        let mut extensions: Vec<_> = DISAMBIGUATIONS_BY_EXTENSION.iter().collect();
+        extensions.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (extension, disambiguations) in extensions {
+            index
+                .entry(extension.to_lowercase())
+                .or_default()
+                .extend(disambiguations.iter().cloned());
+        }
+
+        index
+    });
+
+/// Languages using an extension, matched exactly and otherwise ignoring case
+/// like upstream, so `.PY` is Python while extensions whose case matters, e.g.
+/// `.C` and `.c`, still only match themselves.
+pub fn languages_by_extension(extension: &str) -> Option<&'static BTreeSet<LanguageName>> {
+    get_ignoring_case(
+        &LANGUAGES_BY_EXTENSION,
+        &LANGUAGES_BY_LOWERCASE_EXTENSION,
+        extension,
+    )
+}
+
+/// Disambiguations for an extension, with the same fallback as
+/// [`languages_by_extension`].
+pub fn disambiguations_by_extension(extension: &str) -> Option<&'static Vec<Disambiguation>> {
+    get_ignoring_case(
+        &DISAMBIGUATIONS_BY_EXTENSION,
+        &DISAMBIGUATIONS_BY_LOWERCASE_EXTENSION,
+        extension,
+    )
+}
+
+/// Looks an extension up in `exact`, and only without a match there in the
+/// `folded` index keyed by lowercased extension
+fn get_ignoring_case<'a, V>(
+    exact: &'a HashMap<Extension, V>,
+    folded: &'a HashMap<Extension, V>,
+    extension: &str,
+) -> Option<&'a V> {
+    exact
+        .get(extension)
+        .or_else(|| folded.get(&extension.to_lowercase()))
+}
+
+pub static VENDOR_PATTERNS: Lazy<VendorMatcher> = Lazy::new(|| {
     // Precompile all vendor regex patterns
     //
-    definitions::VENDOR
-        .iter()
-        .filter_map(|pattern| match Regex::new(pattern) {
-            Ok(regex) => Some(regex),
-            Err(e) => {
-                // Should we rather abort? I dunno...
-                // For now we will report to stderr and ignore the pattern
-                //
-                eprintln!("Warning: Failed to compile vendor pattern '{pattern}': {e}");
-                None
-            }
-        })
-        .collect()
+    let (matcher, errors) = VendorMatcher::new(&definitions::VENDOR);
+    for error in errors {
+        // Should we rather abort? I dunno...
+        // For now we will report to stderr and ignore the pattern
+        //
+        eprintln!("Warning: Failed to compile vendor pattern: {error}");
+    }
+    matcher
 });
+
+pub static DOCUMENTATION_PATTERNS: Lazy<VendorMatcher> = Lazy::new(|| {
+    // Documentation patterns are regexes matched against paths, like vendor ones
+    //
+    let (matcher, errors) = VendorMatcher::new(&definitions::DOCUMENTATION);
+    for error in errors {
+        eprintln!("Warning: Failed to compile documentation pattern: {error}");
+    }
+    matcher
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_case_matches_win_over_the_fallback() {
+        // languages.yml has no such pair today, so pin it with a synthetic one
+        let exact: HashMap<Extension, BTreeSet<LanguageName>> = [
+            (".C".to_string(), BTreeSet::from(["C++".to_string()])),
+            (".c".to_string(), BTreeSet::from(["C".to_string()])),
+            (".py".to_string(), BTreeSet::from(["Python".to_string()])),
+        ]
+        .into_iter()
+        .collect();
+        let folded = fold_case(&exact);
+        let lookup = |extension| {
+            get_ignoring_case(&exact, &folded, extension)
+                .map(|names| names.iter().map(String::as_str).collect::<Vec<_>>())
+        };
+
+        assert_eq!(lookup(".C"), Some(vec!["C++"]));
+        assert_eq!(lookup(".c"), Some(vec!["C"]));
+        assert_eq!(lookup(".PY"), Some(vec!["Python"]));
+        assert_eq!(folded[".c"].len(), 2);
+    }
+}
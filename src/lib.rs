@@ -6,36 +6,141 @@
 //! - Exact filename matching
 //! - Content-based heuristic disambiguation
 //! - Vendored file detection
+//! - Parallel repository scanning and language breakdowns
 //!
 //! All language data is embedded at compile-time for zero-cost lookups.
 
+pub mod archive;
+pub mod cache;
 pub mod definitions;
+pub mod diff;
+pub mod embedded;
 pub mod error;
+pub mod generated;
+pub mod git;
+pub mod gitattributes;
 pub(crate) mod indexed;
+pub mod lines;
+pub mod notebook;
+pub mod policy;
+pub mod repository;
+pub mod strategy;
 pub mod utils;
+pub mod vendor;
 
 use linguist_types::{HeuristicRule, Language};
+use std::io::Read;
 use std::path::Path;
+use std::time::{Duration, Instant};
 
 pub use error::LinguistError;
 
 /// Type alias for Results in this crate
 pub type Result<T> = std::result::Result<T, LinguistError>;
 
+/// Default number of bytes read from a file for content-based detection.
+///
+/// Matches the prefix size upstream Linguist inspects for heuristics; content
+/// beyond this point is never read.
+pub const DEFAULT_READ_LIMIT: usize = 50 * 1024;
+
+/// Default number of times a heuristic pattern may backtrack on a file.
+///
+/// The `fancy_regex` default, far more than any pattern needs on real code.
+pub const DEFAULT_BACKTRACK_LIMIT: usize = 1_000_000;
+
 /// Represents a detected programming language.
 ///
 /// Contains both the language name and full language definition with metadata.
+/// More fields may be added, so it is only built by this crate.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct DetectedLanguage {
     /// The name of the detected language (e.g., "Python", "Rust")
     pub name: &'static str,
 
     /// The full language definition with metadata (type, color, extensions, etc.)
     pub definition: &'static Language,
+
+    /// Language of the code embedded in the file, if known. Only set for
+    /// Jupyter notebooks by [`detect_reader`], to their kernel language.
+    pub embedded: Option<&'static str>,
+}
+
+/// Limits on the work heuristics do on a file, so that hostile content can't
+/// stall detection.
+///
+/// A rule whose patterns exceed them is taken as not matching, and reported in
+/// [`HeuristicOutcome::warnings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeuristicLimits {
+    /// Maximum number of times each pattern may backtrack on the content
+    pub backtrack_limit: usize,
+
+    /// Maximum number of bytes of content rules are matched against; anything
+    /// beyond (rounded down to a character boundary) is ignored
+    pub max_content_len: usize,
+
+    /// Wall-clock time after which no further rules are tried on a file, none
+    /// by default. It is checked between rules, so a single pattern is only
+    /// bounded by `backtrack_limit`.
+    pub time_budget: Option<Duration>,
+}
+
+impl Default for HeuristicLimits {
+    fn default() -> Self {
+        HeuristicLimits {
+            backtrack_limit: DEFAULT_BACKTRACK_LIMIT,
+            max_content_len: DEFAULT_READ_LIMIT,
+            time_budget: None,
+        }
+    }
+}
+
+/// Languages found under [`HeuristicLimits`], with the rules given up on.
+///
+/// Returned by [`disambiguate_with_limits`] and, for the whole pipeline, by
+/// [`strategy::Pipeline::detect_with_warnings`].
+#[derive(Debug, Clone, Default)]
+pub struct HeuristicOutcome {
+    /// Languages of the first matching rule, empty if none matched
+    pub languages: Vec<DetectedLanguage>,
+
+    /// A `LinguistError::RegexLimitExceeded` for each rule given up on, and a
+    /// `LinguistError::TimeBudgetExceeded` if the rules left were never tried
+    pub warnings: Vec<LinguistError>,
+}
+
+/// Looks up a language by its name or one of its aliases, ignoring case.
+///
+/// Every language can be found by its lowercased name, and by that name with
+/// spaces replaced by dashes, in addition to the aliases in languages.yml.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::find_language;
+///
+/// assert_eq!(find_language("Rust").unwrap().name, "Rust");
+/// assert_eq!(find_language("common-lisp").unwrap().name, "Common Lisp");
+/// assert_eq!(find_language("js").unwrap().name, "JavaScript");
+/// assert!(find_language("not a language").is_none());
+/// ```
+pub fn find_language(name_or_alias: &str) -> Option<DetectedLanguage> {
+    let lang_name = indexed::LANGUAGES_BY_ALIAS.get(&name_or_alias.to_lowercase())?;
+    let (name, definition) = definitions::LANGUAGES.get_key_value(lang_name)?;
+    Some(DetectedLanguage {
+        name: name.as_str(),
+        definition,
+        embedded: None,
+    })
 }
 
 /// Detects programming language(s) by file extension.
 ///
+/// Extensions are matched exactly, and otherwise ignoring case as upstream
+/// does, so `FOO.PY` is Python while case-significant extensions stay distinct.
+///
 /// Returns a Result containing either:
 /// - Ok(Vec<DetectedLanguage>) with matching languages (empty vec if no matches)
 /// - Err(LinguistError) on error
@@ -58,6 +163,10 @@ pub struct DetectedLanguage {
 /// assert_eq!(langs.len(), 1);
 /// assert_eq!(langs[0].name, "Python");
 ///
+/// // Case only matters when it distinguishes extensions
+/// let langs = detect_language_by_extension("SETUP.PY")?;
+/// assert_eq!(langs[0].name, "Python");
+///
 /// // Some extensions are ambiguous
 /// let langs = detect_language_by_extension("header.h")?;
 /// assert!(langs.iter().any(|lang| lang.name == "C"));
@@ -76,13 +185,14 @@ pub fn detect_language_by_extension<P: AsRef<Path>>(filepath: P) -> Result<Vec<D
     // subsequent disambiguation.
     let extensions = utils::extract_extensions(filename_str);
     for extension in extensions.iter().rev() {
-        if let Some(language_names) = indexed::LANGUAGES_BY_EXTENSION.get(extension) {
+        if let Some(language_names) = indexed::languages_by_extension(extension) {
             let matching_languages: Vec<DetectedLanguage> = language_names
                 .iter()
                 .filter_map(|lang_name| {
                     definitions::LANGUAGES.get(lang_name).map(|lang_def| DetectedLanguage {
                         name: lang_name.as_str(),
                         definition: lang_def,
+                        embedded: None,
                     })
                 })
                 .collect();
@@ -136,6 +246,55 @@ pub fn detect_language_by_filename<P: AsRef<Path>>(filepath: P) -> Result<Vec<De
                 matching_languages.push(DetectedLanguage {
                     name: lang_name.as_str(),
                     definition: lang_def,
+                    embedded: None,
+                });
+            }
+        }
+    }
+
+    Ok(matching_languages)
+}
+
+/// Detects programming language(s) by the interpreter named in a `#!` line.
+///
+/// Only the first line of `content` is looked at; see
+/// [`utils::shebang_interpreter`] for how the interpreter is extracted.
+///
+/// # Arguments
+///
+/// * `content` - The contents of the file, or at least its first line
+///
+/// # Returns
+///
+/// A Result with a vector of DetectedLanguage. Empty if there is no `#!` line
+/// or no language is run by its interpreter.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::detect_language_by_shebang;
+///
+/// let langs = detect_language_by_shebang("#!/usr/bin/env ruby\nputs 'hi'")?;
+/// assert_eq!(langs[0].name, "Ruby");
+///
+/// assert!(detect_language_by_shebang("puts 'hi'")?.is_empty());
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn detect_language_by_shebang(content: &str) -> Result<Vec<DetectedLanguage>> {
+    let Some(interpreter) = utils::shebang_interpreter(content) else {
+        return Ok(vec![]);
+    };
+
+    // Use the interpreter index for O(1) lookup
+    //
+    let mut matching_languages = Vec::new();
+    if let Some(language_names) = indexed::LANGUAGES_BY_INTERPRETER.get(&interpreter) {
+        for lang_name in language_names {
+            if let Some(lang_def) = definitions::LANGUAGES.get(lang_name) {
+                matching_languages.push(DetectedLanguage {
+                    name: lang_name.as_str(),
+                    definition: lang_def,
+                    embedded: None,
                 });
             }
         }
@@ -153,6 +312,11 @@ pub fn detect_language_by_filename<P: AsRef<Path>>(filepath: P) -> Result<Vec<De
 /// Automatically handles UTF-8 BOM (Byte Order Mark) if present at the beginning
 /// of the file content, which is common in files created by Windows/Visual Studio.
 ///
+/// Rules see all of the content, and those exceeding the default backtrack limit
+/// don't match. Use [`disambiguate_with_limits`] to choose the limits and learn
+/// which rules were given up on; with a `max_content_len` of `usize::MAX` it
+/// examines the same content as this function.
+///
 /// # Arguments
 ///
 /// * `filename` - Path or filename to check (used to extract extension)
@@ -177,39 +341,96 @@ pub fn disambiguate<P: AsRef<Path>>(
     filepath: P,
     file_contents: &str,
 ) -> Result<Vec<DetectedLanguage>> {
+    let limits = HeuristicLimits {
+        max_content_len: usize::MAX,
+        ..HeuristicLimits::default()
+    };
+    let outcome = disambiguate_with_limits(filepath, file_contents, &limits)?;
+    Ok(outcome.languages)
+}
+
+/// Same as [`disambiguate`], but runs heuristics under the given limits.
+///
+/// Only the first `limits.max_content_len` bytes of the content are examined.
+/// A rule with a pattern exceeding `limits.backtrack_limit` is taken as not
+/// matching rather than failing detection, and listed in the outcome's
+/// warnings, so one hostile file can't stall a scan. Once `limits.time_budget`
+/// has passed, the rules left are given up on too, and no language is found.
+///
+/// # Returns
+///
+/// A Result containing the languages of the first matching rule and the rules
+/// given up on, or an error if the path is invalid or a pattern is malformed.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::{HeuristicLimits, disambiguate_with_limits};
+///
+/// let limits = HeuristicLimits { max_content_len: 1024, ..HeuristicLimits::default() };
+/// let content = "#include <iostream>\nint main() {}";
+/// let outcome = disambiguate_with_limits("test.h", content, &limits)?;
+/// assert_eq!(outcome.languages[0].name, "C++");
+/// assert!(outcome.warnings.is_empty());
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn disambiguate_with_limits<P: AsRef<Path>>(
+    filepath: P,
+    file_contents: &str,
+    limits: &HeuristicLimits,
+) -> Result<HeuristicOutcome> {
     // Get just the filename
     //
     let filename_str = utils::get_filename_from_path(filepath.as_ref())?;
 
     // Strip UTF-8 BOM if present (common in Windows/Visual Studio files)
-    let content = if file_contents.starts_with('\u{FEFF}') {
-        &file_contents[3..]
-    } else {
-        file_contents
-    };
+    let content = file_contents
+        .strip_prefix('\u{FEFF}')
+        .unwrap_or(file_contents);
+
+    // Rules never see more than the limit, however much content was read
+    //
+    let content = &content[..content.floor_char_boundary(limits.max_content_len)];
+
+    let mut outcome = HeuristicOutcome::default();
+    let started = Instant::now();
 
     // Look up disambiguations using the index for O(1) performance
     for extension in &utils::extract_extensions(filename_str) {
-        if let Some(disambiguations) = indexed::DISAMBIGUATIONS_BY_EXTENSION.get(extension) {
+        if let Some(disambiguations) = indexed::disambiguations_by_extension(extension) {
             // Try each disambiguation that applies to this extension
             for disambiguation in disambiguations {
                 // Try each rule in this disambiguation
                 for rule in &disambiguation.rules {
-                    if evaluate_rule(rule, content)?
-                        && let Some(ref lang_names) = rule.language
+                    if let Some(budget) = limits.time_budget
+                        && started.elapsed() >= budget
                     {
-                        let mut matching_languages = Vec::new();
+                        outcome
+                            .warnings
+                            .push(LinguistError::TimeBudgetExceeded(budget));
+                        return Ok(outcome);
+                    }
+                    let matched = match evaluate_rule(rule, content, limits.backtrack_limit) {
+                        Ok(matched) => matched,
+                        Err(e @ LinguistError::RegexLimitExceeded { .. }) => {
+                            outcome.warnings.push(e);
+                            false
+                        }
+                        Err(e) => return Err(e),
+                    };
+                    if matched && let Some(ref lang_names) = rule.language {
                         for lang_name in lang_names {
                             // If we have a hit - find the language definition by name in the
                             // LANGUAGES struct
                             if let Some(lang_def) = definitions::LANGUAGES.get(lang_name) {
-                                matching_languages.push(DetectedLanguage {
+                                outcome.languages.push(DetectedLanguage {
                                     name: lang_name.as_str(),
                                     definition: lang_def,
+                                    embedded: None,
                                 });
                             }
                         }
-                        return Ok(matching_languages);
+                        return Ok(outcome);
                     }
                 }
             }
@@ -218,17 +439,113 @@ pub fn disambiguate<P: AsRef<Path>>(
 
     // No disambiguation rules matched - this is not an error,
     // just means the file doesn't need disambiguation or no rules applied
-    Ok(vec![])
+    Ok(outcome)
+}
+
+/// Detects the programming language(s) of a file, reading its content from a stream.
+///
+/// Runs the full detection pipeline: exact filename, then extension, and only
+/// when those are ambiguous (or inconclusive) is the content read, for the
+/// interpreter of a `#!` line if neither matched and then heuristics. At most
+/// [`DEFAULT_READ_LIMIT`] bytes are read from `reader`, so memory use stays
+/// flat no matter how large the underlying file is.
+///
+/// A Jupyter notebook is parsed to the end instead, since the kernel language
+/// set as [`DetectedLanguage::embedded`] is recorded in its metadata after the
+/// cells. Memory use still stays flat: only the size of each cell is kept,
+/// not its source.
+///
+/// To add custom steps to the pipeline, see [`strategy::Pipeline`], whose
+/// [`detect_with_warnings`](strategy::Pipeline::detect_with_warnings) also
+/// returns the heuristic rules given up on, which this function drops.
+///
+/// # Arguments
+///
+/// * `filepath` - Path or filename of the content (used for name based detection)
+/// * `reader` - Source of the file contents
+///
+/// # Returns
+///
+/// A Result with a vector of DetectedLanguage. Empty if no matching language is found.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::detect_reader;
+///
+/// let content = "#include <iostream>\nint main() {}";
+/// let langs = detect_reader("test.h", content.as_bytes())?;
+/// assert_eq!(langs[0].name, "C++");
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn detect_reader<P: AsRef<Path>, R: Read>(
+    filepath: P,
+    reader: R,
+) -> Result<Vec<DetectedLanguage>> {
+    detect_reader_with_limit(filepath, reader, DEFAULT_READ_LIMIT)
+}
+
+/// Same as [`detect_reader`], but reads at most `limit` bytes of content.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::detect_reader_with_limit;
+///
+/// // Unambiguous files are detected without reading any content
+/// let langs = detect_reader_with_limit("main.rs", std::io::empty(), 0)?;
+/// assert!(langs.iter().any(|lang| lang.name == "Rust"));
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn detect_reader_with_limit<P: AsRef<Path>, R: Read>(
+    filepath: P,
+    reader: R,
+    limit: usize,
+) -> Result<Vec<DetectedLanguage>> {
+    detect_reader_with_heuristic_limits(filepath, reader, limit, &HeuristicLimits::default())
+}
+
+/// Same as [`detect_reader_with_limit`], but runs heuristics under the given
+/// limits. Rules exceeding them are taken as not matching; see
+/// [`strategy::Pipeline::detect_with_warnings`] to also get them as warnings.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::{HeuristicLimits, detect_reader_with_heuristic_limits};
+///
+/// // With no content to examine, only the C fallback rule of .h files matches
+/// let limits = HeuristicLimits { max_content_len: 0, ..HeuristicLimits::default() };
+/// let content = "#include <iostream>\nint main() {}";
+/// let langs = detect_reader_with_heuristic_limits("test.h", content.as_bytes(), 1024, &limits)?;
+/// assert_eq!(langs[0].name, "C");
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn detect_reader_with_heuristic_limits<P: AsRef<Path>, R: Read>(
+    filepath: P,
+    reader: R,
+    limit: usize,
+    limits: &HeuristicLimits,
+) -> Result<Vec<DetectedLanguage>> {
+    // Exact filenames are the most specific signal, then extensions. Only if
+    // those are ambiguous or unknown do we pay for reading the content, for
+    // the interpreter of a `#!` line and then heuristics
+    //
+    strategy::Pipeline::with_heuristic_limits(*limits).detect(filepath, reader, limit)
 }
 
 /// Helper function to evaluate a single heuristic rule against file contents
 ///
-fn evaluate_rule(rule: &HeuristicRule, file_contents: &str) -> Result<bool> {
+fn evaluate_rule(
+    rule: &HeuristicRule,
+    file_contents: &str,
+    backtrack_limit: usize,
+) -> Result<bool> {
     // If there's an 'and' clause, all sub-rules must match
     //
     if let Some(ref and_rules) = rule.and {
         for sub_rule in and_rules {
-            if !evaluate_rule(sub_rule, file_contents)? {
+            if !evaluate_rule(sub_rule, file_contents, backtrack_limit)? {
                 return Ok(false);
             }
         }
@@ -240,7 +557,7 @@ fn evaluate_rule(rule: &HeuristicRule, file_contents: &str) -> Result<bool> {
     if let Some(ref named_pattern) = rule.named_pattern {
         match definitions::HEURISTICS.named_patterns.get(named_pattern) {
             Some(pattern) => {
-                if !utils::matches_pattern(pattern, file_contents)? {
+                if !utils::matches_pattern_with_limit(pattern, file_contents, backtrack_limit)? {
                     return Ok(false);
                 }
             }
@@ -253,7 +570,7 @@ fn evaluate_rule(rule: &HeuristicRule, file_contents: &str) -> Result<bool> {
     // Check positive pattern
     //
     if let Some(ref pattern) = rule.pattern
-        && !utils::matches_pattern(pattern, file_contents)?
+        && !utils::matches_pattern_with_limit(pattern, file_contents, backtrack_limit)?
     {
         return Ok(false);
     }
@@ -261,7 +578,7 @@ fn evaluate_rule(rule: &HeuristicRule, file_contents: &str) -> Result<bool> {
     // Check negative pattern
     //
     if let Some(ref neg_pattern) = rule.negative_pattern
-        && utils::matches_pattern(neg_pattern, file_contents)?
+        && utils::matches_pattern_with_limit(neg_pattern, file_contents, backtrack_limit)?
     {
         return Ok(false);
     }
@@ -283,7 +600,7 @@ fn evaluate_rule(rule: &HeuristicRule, file_contents: &str) -> Result<bool> {
 /// # Returns
 ///
 /// A Result containing `true` if the file matches any vendor pattern, `false` otherwise.
-/// Returns an error if the path is invalid or regex patterns are malformed.
+/// Returns an error if the path is invalid or a pattern fails to run on it.
 ///
 /// # Examples
 ///
@@ -303,7 +620,78 @@ pub fn is_vendored<P: AsRef<Path>>(filepath: P) -> Result<bool> {
 
     // Check if the path matches any precompiled vendor pattern
     //
-    Ok(indexed::VENDOR_PATTERNS
-        .iter()
-        .any(|regex| regex.is_match(path_str).unwrap_or(false)))
+    Ok(indexed::VENDOR_PATTERNS.first_match(path_str)?.is_some())
+}
+
+/// Checks if a file is documentation, which like vendored files is left out of
+/// repository statistics.
+///
+/// Documentation covers directories like `docs/` and `examples/`, and files like
+/// READMEs and licenses, following documentation.yml.
+///
+/// # Arguments
+///
+/// * `filename` - Path or filename to check, relative to the repository root
+///
+/// # Returns
+///
+/// A Result containing `true` if the file matches any documentation pattern,
+/// `false` otherwise. Returns an error if the path is invalid or a pattern fails
+/// to run on it.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::is_documentation;
+///
+/// assert!(is_documentation("docs/guide/index.js")?);
+/// assert!(is_documentation("README.md")?);
+/// assert!(!is_documentation("src/main.rs")?);
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn is_documentation<P: AsRef<Path>>(filepath: P) -> Result<bool> {
+    let path = filepath.as_ref();
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| LinguistError::InvalidPath(format!("{path:?}")))?;
+
+    Ok(indexed::DOCUMENTATION_PATTERNS
+        .first_match(path_str)?
+        .is_some())
+}
+
+/// Finds the vendor.yml pattern that marks a file of a repository as vendored.
+///
+/// Unlike [`is_vendored`], which matches the path as given, `filepath` is first
+/// made relative to `root` with forward slashes (see
+/// [`utils::root_relative_path`]), so patterns anchored at the repository root
+/// such as `^deps/` also work for absolute paths.
+///
+/// # Arguments
+///
+/// * `root` - Root directory of the repository
+/// * `filepath` - Path of the file, absolute or relative to `root`
+///
+/// # Returns
+///
+/// A Result containing the first matching pattern, or `None` if the file isn't
+/// vendored. Returns an error if the path is outside `root` or isn't valid UTF-8,
+/// or if a pattern fails to run on it.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::vendored_pattern;
+///
+/// let pattern = vendored_pattern("/home/ci/repo", "/home/ci/repo/deps/zlib/zlib.c")?;
+/// assert_eq!(pattern, Some("^deps/"));
+/// assert_eq!(vendored_pattern("/home/ci/repo", "src/main.rs")?, None);
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn vendored_pattern<R: AsRef<Path>, P: AsRef<Path>>(
+    root: R,
+    filepath: P,
+) -> Result<Option<&'static str>> {
+    let relative = utils::root_relative_path(root, filepath)?;
+    indexed::VENDOR_PATTERNS.first_match(&relative)
 }
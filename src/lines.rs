@@ -0,0 +1,253 @@
+//! Counting code, comment and blank lines using the syntax in comments.yml.
+
+use crate::{Result, definitions};
+use linguist_types::CommentSyntax;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Read};
+use std::ops::AddAssign;
+
+/// Number of code, comment and blank lines in a file (or a whole language).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineCounts {
+    /// Lines containing any code, including those that also contain a comment
+    pub code: u64,
+
+    /// Lines containing only comments
+    pub comment: u64,
+
+    /// Lines containing only whitespace
+    pub blank: u64,
+}
+
+impl LineCounts {
+    /// Total number of lines.
+    pub fn total(&self) -> u64 {
+        self.code + self.comment + self.blank
+    }
+}
+
+impl AddAssign for LineCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.code += other.code;
+        self.comment += other.comment;
+        self.blank += other.blank;
+    }
+}
+
+/// Counts the code, comment and blank lines of content in the given language.
+///
+/// The content is streamed line by line, so memory use doesn't depend on its
+/// size. Languages without an entry in comments.yml only have their blank
+/// lines told apart; every other line counts as code.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::lines::count_lines;
+///
+/// let content = "// A comment\nfn main() {} // trailing\n\n/* block\n   comment */\n";
+/// let counts = count_lines(content.as_bytes(), "Rust")?;
+/// assert_eq!(counts.code, 1);
+/// assert_eq!(counts.comment, 3);
+/// assert_eq!(counts.blank, 1);
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn count_lines<R: BufRead>(reader: R, language: &str) -> Result<LineCounts> {
+    CountingReader::new(reader, language).finish()
+}
+
+/// Syntax of languages without an entry in comments.yml
+static NO_SYNTAX: Lazy<CommentSyntax> = Lazy::new(CommentSyntax::default);
+
+/// Reader passing content through unchanged while counting its lines, so that
+/// content also read for something else only needs to be read once.
+pub(crate) struct CountingReader<R> {
+    inner: R,
+    counter: LineCounter<'static>,
+    counts: LineCounts,
+
+    /// The incomplete line read so far
+    line: Vec<u8>,
+}
+
+impl<R: Read> CountingReader<R> {
+    pub(crate) fn new(inner: R, language: &str) -> Self {
+        let syntax = definitions::COMMENTS.get(language).unwrap_or(&NO_SYNTAX);
+        CountingReader {
+            inner,
+            counter: LineCounter::new(syntax),
+            counts: LineCounts::default(),
+            line: Vec::new(),
+        }
+    }
+
+    /// Reads the rest of the content, returning the counts of all of it.
+    pub(crate) fn finish(mut self) -> Result<LineCounts> {
+        io::copy(&mut self, &mut io::sink())?;
+        let line = std::mem::take(&mut self.line);
+        if !line.is_empty() {
+            self.count(&line);
+        }
+        Ok(self.counts)
+    }
+
+    fn count(&mut self, line: &[u8]) {
+        match self.counter.classify(&String::from_utf8_lossy(line)) {
+            LineKind::Code => self.counts.code += 1,
+            LineKind::Comment => self.counts.comment += 1,
+            LineKind::Blank => self.counts.blank += 1,
+        }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        for chunk in buf[..n].split_inclusive(|byte| *byte == b'\n') {
+            self.line.extend_from_slice(chunk);
+            if chunk.ends_with(b"\n") {
+                let mut line = std::mem::take(&mut self.line);
+                self.count(&line);
+                line.clear();
+                self.line = line;
+            }
+        }
+        Ok(n)
+    }
+}
+
+enum LineKind {
+    Code,
+    Comment,
+    Blank,
+}
+
+/// Tracks comments and strings that continue across lines
+struct LineCounter<'a> {
+    syntax: &'a CommentSyntax,
+
+    /// The (open, close) markers of the block comment we're in, and its depth
+    block: Option<(&'a str, &'a str, usize)>,
+
+    /// Closing delimiter of the string we're in, and whether it spans lines
+    string: Option<(&'a str, bool)>,
+}
+
+impl<'a> LineCounter<'a> {
+    fn new(syntax: &'a CommentSyntax) -> Self {
+        LineCounter {
+            syntax,
+            block: None,
+            string: None,
+        }
+    }
+
+    fn classify(&mut self, line: &str) -> LineKind {
+        if line.trim().is_empty() {
+            return LineKind::Blank;
+        }
+
+        let mut code = false;
+        let mut comment = false;
+        let mut i = 0;
+
+        while let Some(c) = line[i..].chars().next() {
+            let rest = &line[i..];
+
+            if let Some((delimiter, _)) = self.string {
+                code = true;
+                if c == '\\' {
+                    // Skip the escaped character too
+                    i += c.len_utf8();
+                    i += line[i..].chars().next().map_or(0, char::len_utf8);
+                } else if rest.starts_with(delimiter) {
+                    self.string = None;
+                    i += delimiter.len();
+                } else {
+                    i += c.len_utf8();
+                }
+                continue;
+            }
+
+            if let Some((open, close, depth)) = self.block {
+                comment = true;
+                if self.syntax.nested && rest.starts_with(open) {
+                    self.block = Some((open, close, depth + 1));
+                    i += open.len();
+                } else if rest.starts_with(close) {
+                    self.block = (depth > 1).then_some((open, close, depth - 1));
+                    i += close.len();
+                } else {
+                    i += c.len_utf8();
+                }
+                continue;
+            }
+
+            if c.is_whitespace() {
+                i += c.len_utf8();
+                continue;
+            }
+
+            // Block comments are checked first, as their markers can start
+            // with a line comment marker (e.g. `--[[` in Lua)
+            if let Some((open, close)) = self
+                .syntax
+                .block_comments
+                .iter()
+                .find(|(open, _)| rest.starts_with(open.as_str()))
+            {
+                comment = true;
+                self.block = Some((open, close, 1));
+                i += open.len();
+                continue;
+            }
+
+            if self
+                .syntax
+                .line_comments
+                .iter()
+                .any(|marker| rest.starts_with(marker.as_str()))
+            {
+                comment = true;
+                break;
+            }
+
+            // Multiline delimiters first, so `"""` isn't mistaken for `"`
+            code = true;
+            let multiline = self
+                .syntax
+                .multiline_strings
+                .iter()
+                .find(|delimiter| rest.starts_with(delimiter.as_str()))
+                .map(|delimiter| (delimiter.as_str(), true));
+            let single = || {
+                self.syntax
+                    .strings
+                    .iter()
+                    .find(|delimiter| rest.starts_with(delimiter.as_str()))
+                    .map(|delimiter| (delimiter.as_str(), false))
+            };
+            match multiline.or_else(single) {
+                Some((delimiter, spans_lines)) => {
+                    self.string = Some((delimiter, spans_lines));
+                    i += delimiter.len();
+                }
+                None => i += c.len_utf8(),
+            }
+        }
+
+        // Only multiline strings carry over to the next line
+        if let Some((_, false)) = self.string {
+            self.string = None;
+        }
+
+        if code {
+            LineKind::Code
+        } else if comment {
+            LineKind::Comment
+        } else {
+            LineKind::Blank
+        }
+    }
+}
@@ -1,119 +1,508 @@
 mod cli;
+mod config;
+mod report;
+mod serve;
+mod stdio;
 
-use clap::Parser;
-use cli::{Cli, DetectionMethods};
-use linguist::{
-    detect_language_by_extension, detect_language_by_filename, disambiguate, is_vendored,
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use cli::{
+    CheckArgs, Cli, Command, DiffArgs, Format, InfoArgs, LanguagesArgs, LsArgs, STDIN_FILE,
+    ServeArgs, StatsArgs,
 };
+use config::Config;
+use linguist::cache::Cache;
+use linguist::diff;
+use linguist::git;
+use linguist::policy::{POLICY_FILENAME, Policy};
+use linguist::repository::{self, ScanOptions};
+use linguist::{HeuristicLimits, definitions, find_language};
+use report::{
+    BreakdownReport, CheckReport, DiffReport, FileReportPrinter, LanguageReport, ListedFile,
+};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
 use std::process;
 
 fn main() {
     let cli = Cli::parse();
-    let methods = cli.detection_methods();
+    let config = load_config(&cli);
+
+    // Flags win over the configuration file
+    let format = cli.format.or(config.format).unwrap_or(Format::Text);
+
+    let limits = cli.heuristic_limits();
+
+    match cli.command {
+        Some(Command::Stats(ref args)) => run_stats(args, &config, cli.read_limit, limits, format),
+        Some(Command::Diff(ref args)) => run_diff(args, &config, cli.read_limit, limits, format),
+        Some(Command::Serve(ref args)) => run_serve(args, &cli, &config),
+        Some(Command::Ls(ref args)) => run_ls(args, &config, cli.read_limit, limits, format),
+        Some(Command::Check(ref args)) => run_check(args, &config, cli.read_limit, limits, format),
+        Some(Command::Languages(ref args)) => run_languages(args, format),
+        Some(Command::Info(ref args)) => run_info(args, format),
+        None if cli.stdio => run_stdio(&cli, &config),
+        None => run_files(&cli, &config, format),
+    }
+}
+
+/// Loads the configuration given by --config, or else the `.linguist.toml`
+/// found from the scanned directory upwards
+fn load_config(cli: &Cli) -> Config {
+    if cli.no_config {
+        return Config::default();
+    }
+    let path = match cli.config {
+        Some(ref path) => path.clone(),
+        None => match Config::discover(Path::new(scan_root(cli))) {
+            Some(path) => path,
+            None => return Config::default(),
+        },
+    };
+    match Config::load(&path) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error loading configuration {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    }
+}
 
-    let mut any_success = false;
-    let mut any_error = false;
+/// The directory a command scans, where its configuration is looked for
+fn scan_root(cli: &Cli) -> &str {
+    match cli.command {
+        Some(Command::Stats(ref args)) => &args.path,
+        Some(Command::Ls(ref args)) => &args.path,
+        Some(Command::Check(ref args)) => &args.path,
+        Some(Command::Diff(ref args)) => args.repo.as_deref().unwrap_or(&args.after),
+        _ => ".",
+    }
+}
+
+/// Scan options with the configured path filter and overrides
+fn scan_options(
+    config: &Config,
+    jobs: Option<usize>,
+    read_limit: usize,
+    heuristic_limits: HeuristicLimits,
+) -> ScanOptions {
+    ScanOptions {
+        jobs: jobs.unwrap_or_else(repository::default_jobs),
+        read_limit,
+        heuristic_limits,
+        filter: config
+            .filter()
+            .expect("Checked when loading the configuration"),
+        overrides: config
+            .overrides()
+            .expect("Checked when loading the configuration"),
+        ..ScanOptions::default()
+    }
+}
+
+fn run_files(cli: &Cli, config: &Config, format: Format) {
+    let methods = cli.detection_methods(config.detection_methods());
+    let overrides = config
+        .overrides()
+        .expect("Checked when loading the configuration");
+    let read_limit = cli.read_limit;
+    let limits = cli.heuristic_limits();
+    let inputs = cli.inputs();
 
-    for filepath in &cli.files {
-        match process_file(filepath, methods) {
-            Ok(()) => any_success = true,
-            Err(e) => {
-                eprintln!("Error processing {}: {}", filepath, e);
-                any_error = true;
+    if cli.name.is_some() && !inputs.contains(&STDIN_FILE) {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--name can only be used with --stdin or a \"-\" FILE",
+            )
+            .exit();
+    }
+    if cli.files_from.as_deref() == Some(STDIN_FILE) && inputs.contains(&STDIN_FILE) {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "stdin can't be read both as content (\"-\" FILE) and as --files-from list",
+            )
+            .exit();
+    }
+
+    // Stdin can only be read once, so its content is shared by every "-"
+    let mut stdin_content: Option<Vec<u8>> = None;
+
+    let mut printer = FileReportPrinter::new(format, cli.explain);
+    for filepath in inputs {
+        let mut report = if filepath == STDIN_FILE {
+            let name = cli.name.as_deref().unwrap_or(STDIN_FILE);
+            if stdin_content.is_none() {
+                match read_stdin(name, read_limit) {
+                    Ok(content) => stdin_content = Some(content),
+                    Err(e) => {
+                        eprintln!("Error reading stdin: {}", e);
+                        process::exit(1);
+                    }
+                }
             }
+            let content = stdin_content.as_deref().unwrap_or_default();
+            report::detect_file(name, methods, read_limit, &limits, || Ok(content))
+        } else {
+            report::detect_file(filepath, methods, read_limit, &limits, || {
+                Ok(File::open(filepath)?)
+            })
+        };
+        report.apply_overrides(&overrides, methods);
+        printer.print(&report);
+    }
+
+    // Listed paths are detected as they are read, never collected
+    if let Some(ref list) = cli.files_from {
+        let result = open_list(list).and_then(|reader| {
+            for_each_listed_path(reader, cli.null, |filepath| {
+                let mut report =
+                    report::detect_file(filepath, methods, read_limit, &limits, || {
+                        Ok(File::open(filepath)?)
+                    });
+                report.apply_overrides(&overrides, methods);
+                printer.print(&report);
+            })
+        });
+        if let Err(e) = result {
+            eprintln!("Error reading file list {}: {}", list, e);
+            process::exit(1);
+        }
+    }
+
+    printer.finish();
+}
+
+/// Opens a --files-from list, where "-" is stdin
+fn open_list(list: &str) -> io::Result<Box<dyn BufRead>> {
+    if list == STDIN_FILE {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(list)?)))
+    }
+}
+
+/// Calls `f` on each path of a newline or NUL separated list as it is read.
+///
+/// Empty entries are skipped, as are paths that aren't valid UTF-8 since
+/// detection works on `&str` paths.
+fn for_each_listed_path<F: FnMut(&str)>(
+    mut reader: impl BufRead,
+    null: bool,
+    mut f: F,
+) -> io::Result<()> {
+    let separator = if null { b'\0' } else { b'\n' };
+    let mut entry = Vec::new();
+    loop {
+        entry.clear();
+        if reader.read_until(separator, &mut entry)? == 0 {
+            return Ok(());
+        }
+        if entry.last() == Some(&separator) {
+            entry.pop();
+        }
+        if !null && entry.last() == Some(&b'\r') {
+            entry.pop();
+        }
+        if entry.is_empty() {
+            continue;
+        }
+        match std::str::from_utf8(&entry) {
+            Ok(path) => f(path),
+            Err(_) => eprintln!(
+                "Warning: Skipping non-UTF-8 path {}",
+                String::from_utf8_lossy(&entry)
+            ),
         }
     }
+}
 
-    // Exit with error code if all files failed
-    if !any_success && any_error {
+/// Reads the content piped to stdin: in full for notebooks, whose metadata
+/// comes after their cells, and otherwise only as much as detection reads
+fn read_stdin(name: &str, read_limit: usize) -> io::Result<Vec<u8>> {
+    let limit = if report::is_notebook(name) {
+        u64::MAX
+    } else {
+        read_limit as u64
+    };
+    let mut content = Vec::new();
+    io::stdin().lock().take(limit).read_to_end(&mut content)?;
+    Ok(content)
+}
+
+fn run_stdio(cli: &Cli, config: &Config) {
+    let jobs = cli.jobs.unwrap_or_else(repository::default_jobs);
+    let methods = cli.detection_methods(config.detection_methods());
+    let overrides = config
+        .overrides()
+        .expect("Checked when loading the configuration");
+    if let Err(e) = stdio::run(
+        methods,
+        cli.read_limit,
+        cli.heuristic_limits(),
+        &overrides,
+        jobs,
+    ) {
+        eprintln!("Error handling requests: {}", e);
         process::exit(1);
     }
 }
 
-fn process_file(
-    filepath: &str,
-    methods: DetectionMethods,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Always check if vendored
-    let vendored = is_vendored(filepath).unwrap_or(false);
-    let vendored_status = if vendored { "[vendored]" } else { "" };
-
-    let mut found_any = false;
-
-    // Detect by extension
-    if methods.by_extension {
-        match detect_language_by_extension(filepath) {
-            Ok(languages) if !languages.is_empty() => {
-                found_any = true;
-                let names: Vec<&str> = languages.iter().map(|l| l.name).collect();
-                println!(
-                    "{}: {} (by extension) {}",
-                    filepath,
-                    names.join(", "),
-                    vendored_status
+fn run_stats(
+    args: &StatsArgs,
+    config: &Config,
+    read_limit: usize,
+    limits: HeuristicLimits,
+    format: Format,
+) {
+    let cache_dir = match args.cache_dir {
+        Some(ref dir) => Some(dir.clone()),
+        None if args.cache => match Cache::default_dir(&args.path) {
+            Some(dir) => Some(dir),
+            None => {
+                eprintln!(
+                    "Error: {} is not a git repository, use --cache-dir",
+                    args.path
                 );
+                process::exit(1);
             }
-            Ok(_) => {} // No matches, that's ok
-            Err(e) => eprintln!(
-                "Warning: Error detecting by extension for {}: {}",
-                filepath, e
-            ),
+        },
+        None => None,
+    };
+    let options = ScanOptions {
+        cache_dir,
+        count_lines: args.lines,
+        embedded_languages: args.embedded,
+        // Only totals are printed, which vendored and documentation files never
+        // count towards
+        prune_vendored: true,
+        count_pruned_bytes: args.count_pruned,
+        ..scan_options(config, args.jobs, read_limit, limits)
+    };
+
+    if let Err(e) = print_stats(&args.path, args.rev.as_deref(), &options, format) {
+        eprintln!("Error scanning {}: {}", args.path, e);
+        process::exit(1);
+    }
+}
+
+fn run_diff(
+    args: &DiffArgs,
+    config: &Config,
+    read_limit: usize,
+    limits: HeuristicLimits,
+    format: Format,
+) {
+    let options = scan_options(config, args.jobs, read_limit, limits);
+
+    let result = match args.repo {
+        Some(ref repo) => diff::diff_revisions(repo, &args.before, &args.after, &options),
+        None => diff::diff_directories(&args.before, &args.after, &options),
+    };
+    match result {
+        Ok(changes) => {
+            for (side, errors) in [
+                (&args.before, &changes.before_errors),
+                (&args.after, &changes.after_errors),
+            ] {
+                for (path, error) in errors {
+                    eprintln!(
+                        "Warning: Failed to classify {} in {}: {}",
+                        path.display(),
+                        side,
+                        error
+                    );
+                }
+            }
+            let report = DiffReport::new(&changes);
+            match format {
+                Format::Text => report.print_text(),
+                Format::Json => println!("{}", report::to_json(&report)),
+            }
+        }
+        Err(e) => {
+            eprintln!("Error comparing {} and {}: {}", args.before, args.after, e);
+            process::exit(1);
         }
     }
+}
 
-    // Detect by filename
-    if methods.by_filename {
-        match detect_language_by_filename(filepath) {
-            Ok(languages) if !languages.is_empty() => {
-                found_any = true;
-                let names: Vec<&str> = languages.iter().map(|l| l.name).collect();
-                println!(
-                    "{}: {} (by filename) {}",
-                    filepath,
-                    names.join(", "),
-                    vendored_status
-                );
+fn run_serve(args: &ServeArgs, cli: &Cli, config: &Config) {
+    let jobs = args.jobs.unwrap_or_else(repository::default_jobs);
+    let methods = cli.detection_methods(config.detection_methods());
+    let options = scan_options(config, None, cli.read_limit, cli.heuristic_limits());
+    if let Err(e) = serve::run(&args.bind, jobs, methods, &options) {
+        eprintln!("Error serving on {}: {}", args.bind, e);
+        process::exit(1);
+    }
+}
+
+fn run_ls(
+    args: &LsArgs,
+    config: &Config,
+    read_limit: usize,
+    limits: HeuristicLimits,
+    format: Format,
+) {
+    // Names are resolved up front so a typo fails before the tree is walked
+    let mut wanted = Vec::new();
+    for name in &args.languages {
+        match find_language(name) {
+            Some(language) => wanted.push(language.name),
+            None => {
+                eprintln!("Error: Unknown language {}", name);
+                process::exit(1);
             }
-            Ok(_) => {} // No matches, that's ok
-            Err(e) => eprintln!(
-                "Warning: Error detecting by filename for {}: {}",
-                filepath, e
-            ),
         }
     }
 
-    // Detect by content
-    if methods.by_content {
-        match std::fs::read_to_string(filepath) {
-            Ok(content) => {
-                match disambiguate(filepath, &content) {
-                    Ok(languages) if !languages.is_empty() => {
-                        found_any = true;
-                        let names: Vec<&str> = languages.iter().map(|l| l.name).collect();
-                        println!(
-                            "{}: {} (by content) {}",
-                            filepath,
-                            names.join(", "),
-                            vendored_status
-                        );
-                    }
-                    Ok(_) => {} // No matches, that's ok
-                    Err(e) => eprintln!(
-                        "Warning: Error during disambiguation for {}: {}",
-                        filepath, e
-                    ),
+    let options = ScanOptions {
+        // Markers are only needed to leave generated files out
+        generated_markers: !args.include_generated,
+        ..scan_options(config, args.jobs, read_limit, limits)
+    };
+    let breakdown = match repository::analyze(&args.path, &options) {
+        Ok(breakdown) => breakdown,
+        Err(e) => {
+            eprintln!("Error scanning {}: {}", args.path, e);
+            process::exit(1);
+        }
+    };
+    for (path, error) in &breakdown.errors {
+        eprintln!("Warning: Failed to classify {}: {}", path.display(), error);
+    }
+
+    // Paths inside an archive can't be joined to the archive's own path
+    let root = Path::new(&args.path);
+    let root = if root.is_dir() { root } else { Path::new("") };
+
+    let files: Vec<ListedFile> = breakdown
+        .files
+        .iter()
+        .filter(|entry| args.include_vendored || !entry.vendored)
+        .filter(|entry| args.include_generated || !entry.generated)
+        .filter_map(|entry| {
+            let language = entry.language?;
+            let group = definitions::LANGUAGES
+                .get(language)
+                .and_then(|definition| definition.group.as_deref());
+            let matches = wanted
+                .iter()
+                .any(|name| *name == language || Some(*name) == group);
+            matches.then(|| ListedFile {
+                path: root.join(&entry.path).to_string_lossy().into_owned(),
+                language,
+            })
+        })
+        .collect();
+
+    match format {
+        Format::Text => {
+            let separator = if args.null { '\0' } else { '\n' };
+            let mut stdout = io::stdout().lock();
+            for file in &files {
+                // A closed pipe (e.g. `| head`) just ends the listing
+                if write!(stdout, "{}{}", file.path, separator).is_err() {
+                    break;
                 }
             }
-            Err(e) => {
-                eprintln!("Warning: Failed to read file {}: {}", filepath, e);
-            }
         }
+        Format::Json => println!("{}", report::to_json(&files)),
+    }
+}
+
+fn run_check(
+    args: &CheckArgs,
+    config: &Config,
+    read_limit: usize,
+    limits: HeuristicLimits,
+    format: Format,
+) {
+    let policy_path = match args.policy {
+        Some(ref path) => path.clone(),
+        None => Path::new(&args.path).join(POLICY_FILENAME),
+    };
+    let policy = match Policy::load(&policy_path) {
+        Ok(policy) => policy,
+        Err(e) => {
+            eprintln!("Error loading policy {}: {}", policy_path.display(), e);
+            process::exit(1);
+        }
+    };
+
+    let options = scan_options(config, args.jobs, read_limit, limits);
+    let result = match args.rev {
+        Some(ref rev) => git::analyze_revision(&args.path, rev, &options),
+        None => repository::analyze(&args.path, &options),
+    };
+    let breakdown = match result {
+        Ok(breakdown) => breakdown,
+        Err(e) => {
+            eprintln!("Error scanning {}: {}", args.path, e);
+            process::exit(1);
+        }
+    };
+    for (path, error) in &breakdown.errors {
+        eprintln!("Warning: Failed to classify {}: {}", path.display(), error);
+    }
+
+    let report = CheckReport::new(&policy.check(&breakdown));
+    match format {
+        Format::Text => report.print_text(),
+        Format::Json => println!("{}", report::to_json(&report)),
+    }
+    if !report.passed {
+        process::exit(1);
     }
+}
+
+fn run_languages(args: &LanguagesArgs, format: Format) {
+    let mut languages: Vec<LanguageReport> = definitions::LANGUAGES
+        .iter()
+        .filter(|(name, language)| args.matches(name, language))
+        .map(|(name, definition)| LanguageReport {
+            name: name.as_str(),
+            definition,
+        })
+        .collect();
+    languages.sort_by_key(|language| language.name.to_lowercase());
 
-    // If no language was detected by any method, report as unknown
-    if !found_any {
-        println!("{}: Unknown {}", filepath, vendored_status);
+    match format {
+        Format::Text => report::print_language_list(&languages),
+        Format::Json => println!("{}", report::to_json(&languages)),
     }
+}
 
+fn run_info(args: &InfoArgs, format: Format) {
+    let Some(language) = find_language(&args.name) else {
+        eprintln!("Error: Unknown language {}", args.name);
+        process::exit(1);
+    };
+
+    let report = LanguageReport::from(language);
+    match format {
+        Format::Text => report.print_text(),
+        Format::Json => println!("{}", report::to_json(&report)),
+    }
+}
+
+fn print_stats(
+    path: &str,
+    rev: Option<&str>,
+    options: &ScanOptions,
+    format: Format,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let breakdown = match rev {
+        Some(rev) => git::analyze_revision(path, rev, options)?,
+        None => repository::analyze(path, options)?,
+    };
+
+    let report = BreakdownReport::new(&breakdown, options.count_lines);
+    match format {
+        Format::Text => report.print_text(options.count_lines),
+        Format::Json => println!("{}", report::to_json(&report)),
+    }
     Ok(())
 }
@@ -0,0 +1,198 @@
+//! Jupyter notebook inspection.
+//!
+//! A `.ipynb` file is detected as "Jupyter Notebook", but the language its code
+//! cells are written in is recorded in the notebook's metadata. The kernel's
+//! `metadata.kernelspec.language` is preferred, falling back to
+//! `metadata.language_info.name`, and either is resolved through the language
+//! names and aliases of languages.yml.
+
+use crate::{DetectedLanguage, LinguistError, Result, find_language};
+use serde::Deserialize;
+use serde::de::{Deserializer, SeqAccess, Visitor};
+use std::fmt;
+use std::io::{BufReader, Read};
+
+/// Name of the language notebooks are detected as
+pub const NOTEBOOK_LANGUAGE: &str = "Jupyter Notebook";
+
+/// What was learned from a notebook's content.
+#[derive(Debug, Clone)]
+pub struct NotebookInfo {
+    /// Language of the notebook's code cells, if the metadata names a known one
+    pub language: Option<DetectedLanguage>,
+
+    /// Total size in bytes of the source of all code cells
+    pub code_bytes: u64,
+}
+
+#[derive(Deserialize)]
+struct Notebook {
+    #[serde(default)]
+    metadata: Metadata,
+    #[serde(default)]
+    cells: CodeBytes,
+}
+
+#[derive(Default, Deserialize)]
+struct Metadata {
+    kernelspec: Option<KernelSpec>,
+    language_info: Option<LanguageInfo>,
+}
+
+#[derive(Deserialize)]
+struct KernelSpec {
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LanguageInfo {
+    name: Option<String>,
+}
+
+/// Total size of the source of the code cells, added up one cell at a time so
+/// that the sources are never all kept in memory
+#[derive(Default)]
+struct CodeBytes(u64);
+
+impl<'de> Deserialize<'de> for CodeBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct CellsVisitor;
+
+        impl<'de> Visitor<'de> for CellsVisitor {
+            type Value = CodeBytes;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a list of cells")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> std::result::Result<CodeBytes, A::Error> {
+                let mut total = 0;
+                while let Some(cell) = seq.next_element::<Cell>()? {
+                    if cell.cell_type == "code" {
+                        total += cell.source.0;
+                    }
+                }
+                Ok(CodeBytes(total))
+            }
+        }
+
+        deserializer.deserialize_seq(CellsVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+struct Cell {
+    cell_type: String,
+    #[serde(default)]
+    source: SourceLen,
+}
+
+/// Size of a cell source, which is either a single string or a list of lines.
+/// Only the size is kept, not the source itself.
+#[derive(Default)]
+struct SourceLen(u64);
+
+impl<'de> Deserialize<'de> for SourceLen {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(SourceVisitor { lines: true })
+    }
+}
+
+/// Measures a source string, or with `lines` set a list of them
+struct SourceVisitor {
+    lines: bool,
+}
+
+impl<'de> Visitor<'de> for SourceVisitor {
+    type Value = SourceLen;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.lines {
+            f.write_str("a string or a list of strings")
+        } else {
+            f.write_str("a string")
+        }
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> std::result::Result<SourceLen, E> {
+        Ok(SourceLen(v.len() as u64))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<SourceLen, A::Error> {
+        if !self.lines {
+            return Err(serde::de::Error::invalid_type(
+                serde::de::Unexpected::Seq,
+                &self,
+            ));
+        }
+        let mut total = 0;
+        while let Some(line) = seq.next_element::<Line>()? {
+            total += line.0.0;
+        }
+        Ok(SourceLen(total))
+    }
+}
+
+/// One line of a source given as a list of lines
+struct Line(SourceLen);
+
+impl<'de> Deserialize<'de> for Line {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer
+            .deserialize_str(SourceVisitor { lines: false })
+            .map(Line)
+    }
+}
+
+/// Inspects a notebook, reading its kernel language and the size of its code.
+///
+/// The notebook is parsed in full, since its metadata usually comes after the
+/// cells, but memory use stays flat: cell outputs (often most of the file) are
+/// skipped rather than loaded, and only the size of each cell's source is kept. Returns [`LinguistError::InvalidNotebook`] if the content isn't a
+/// notebook.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::notebook::inspect_notebook;
+///
+/// let notebook = r#"{
+///     "cells": [{"cell_type": "code", "source": ["print(1)\n", "print(2)"]}],
+///     "metadata": {"kernelspec": {"language": "python", "name": "python3"}},
+///     "nbformat": 4
+/// }"#;
+/// let info = inspect_notebook(notebook.as_bytes())?;
+/// assert_eq!(info.language.unwrap().name, "Python");
+/// assert_eq!(info.code_bytes, 17);
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn inspect_notebook<R: Read>(reader: R) -> Result<NotebookInfo> {
+    let notebook: Notebook = serde_json::from_reader(BufReader::new(reader)).map_err(|e| {
+        if e.is_io() {
+            LinguistError::Io(e.to_string())
+        } else {
+            LinguistError::InvalidNotebook(e.to_string())
+        }
+    })?;
+
+    let kernel_language = notebook
+        .metadata
+        .kernelspec
+        .and_then(|kernelspec| kernelspec.language);
+    let info_language = notebook
+        .metadata
+        .language_info
+        .and_then(|language_info| language_info.name);
+    let language = [kernel_language, info_language]
+        .into_iter()
+        .flatten()
+        .find_map(|name| find_language(&name));
+
+    Ok(NotebookInfo {
+        language,
+        code_bytes: notebook.cells.0,
+    })
+}
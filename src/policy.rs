@@ -0,0 +1,383 @@
+//! Language policies, checked against a repository breakdown in CI.
+//!
+//! A policy is a YAML file listing the languages a repository may contain:
+//!
+//! ```yaml
+//! # Languages allowed anywhere (omit to allow all but the denied ones)
+//! allow: [Rust, Python, Shell]
+//! deny: [Perl]
+//!
+//! # Largest share of the code each language may make up, in percent
+//! max_percentage:
+//!   Python: 20
+//!
+//! # Vendored files must live under one of these globs (omit to allow anywhere)
+//! vendored_paths: ["third_party/"]
+//!
+//! # Rules for parts of the tree, overriding the ones above
+//! paths:
+//!   - glob: "scripts/"
+//!     allow: [Shell]
+//!   - glob: "legacy/"
+//!     allow: [Perl]
+//! ```
+//!
+//! Globs follow `.gitattributes` rules, like the `include` and `exclude` globs
+//! of [`PathFilter`](crate::repository::PathFilter): one ending in a slash
+//! matches everything below a directory, and one without a slash matches file
+//! names at any depth.
+//!
+//! Languages are given by name or alias, and also match the languages grouped
+//! under them. A file is judged by the last `paths` rule matching it that
+//! decides on its language, falling back to the global lists: a rule with an
+//! `allow` list decides on every language, one with only a `deny` list only on
+//! the languages it denies. `allow` lists only restrict programming and markup
+//! languages, so data and prose such as JSON or Markdown needn't be listed.
+//! Vendored files are exempt from the language lists, and only count towards
+//! `vendored_paths`.
+
+use crate::repository::{Breakdown, compile_globs};
+use crate::{LinguistError, Result, definitions, find_language};
+use globset::GlobSet;
+use linguist_types::LanguageType;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the policy file looked for at the root of a repository
+pub const POLICY_FILENAME: &str = ".linguist-policy.yml";
+
+/// A compiled language policy.
+#[derive(Debug, Clone)]
+pub struct Policy {
+    global: Rule,
+    paths: Vec<PathRule>,
+    max_percentage: Vec<(&'static str, f64)>,
+    vendored_paths: Option<GlobSet>,
+}
+
+/// Allowed and denied languages, globally or for one glob
+#[derive(Debug, Clone)]
+struct Rule {
+    allow: Option<Vec<&'static str>>,
+    deny: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone)]
+struct PathRule {
+    glob: String,
+    matcher: GlobSet,
+    rule: Rule,
+}
+
+/// A policy file as written
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    allow: Option<Vec<String>>,
+    #[serde(default)]
+    deny: Vec<String>,
+    #[serde(default)]
+    max_percentage: BTreeMap<String, f64>,
+    vendored_paths: Option<Vec<String>>,
+    #[serde(default)]
+    paths: Vec<PathRuleFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PathRuleFile {
+    glob: String,
+    allow: Option<Vec<String>>,
+    #[serde(default)]
+    deny: Vec<String>,
+}
+
+/// A way in which a repository breaks its policy.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// A file is in a language its rule denies. `rule` is the glob of the
+    /// deciding `paths` rule, or `None` for the global lists.
+    DeniedLanguage {
+        path: PathBuf,
+        language: &'static str,
+        rule: Option<String>,
+    },
+
+    /// A file is in a language missing from the `allow` list of its rule
+    UnlistedLanguage {
+        path: PathBuf,
+        language: &'static str,
+        rule: Option<String>,
+    },
+
+    /// A vendored file lies outside every `vendored_paths` glob
+    VendoredOutsideApprovedPaths { path: PathBuf },
+
+    /// A language makes up more of the code than its `max_percentage`
+    AboveMaxPercentage {
+        language: &'static str,
+        percentage: f64,
+        max: f64,
+    },
+}
+
+impl Violation {
+    /// The file breaking the policy, if the violation is about a single file.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Violation::DeniedLanguage { path, .. }
+            | Violation::UnlistedLanguage { path, .. }
+            | Violation::VendoredOutsideApprovedPaths { path } => Some(path),
+            Violation::AboveMaxPercentage { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scope = |rule: &Option<String>| match rule {
+            Some(glob) => format!("in {glob}"),
+            None => "by the policy".to_string(),
+        };
+        match self {
+            Violation::DeniedLanguage {
+                path,
+                language,
+                rule,
+            } => {
+                write!(
+                    f,
+                    "{}: {language} is denied {}",
+                    path.display(),
+                    scope(rule)
+                )
+            }
+            Violation::UnlistedLanguage {
+                path,
+                language,
+                rule,
+            } => {
+                write!(
+                    f,
+                    "{}: {language} is not allowed {}",
+                    path.display(),
+                    scope(rule)
+                )
+            }
+            Violation::VendoredOutsideApprovedPaths { path } => {
+                write!(
+                    f,
+                    "{}: vendored code outside the approved paths",
+                    path.display()
+                )
+            }
+            Violation::AboveMaxPercentage {
+                language,
+                percentage,
+                max,
+            } => {
+                write!(
+                    f,
+                    "{language} is {percentage:.2}% of the code, above the {max}% limit"
+                )
+            }
+        }
+    }
+}
+
+impl Policy {
+    /// Parses a policy from YAML.
+    ///
+    /// Unknown keys, languages and invalid globs are errors, so that typos
+    /// don't silently weaken the policy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use linguist::policy::Policy;
+    ///
+    /// let policy = Policy::from_yaml("deny: [perl]\nmax_percentage: {JavaScript: 10}")?;
+    /// assert!(Policy::from_yaml("deny: [not-a-language]").is_err());
+    /// # Ok::<(), linguist::LinguistError>(())
+    /// ```
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let file: PolicyFile = serde_yaml_ng::from_str(yaml)
+            .map_err(|e| LinguistError::InvalidPolicy(e.to_string()))?;
+
+        let mut paths = Vec::with_capacity(file.paths.len());
+        for path in file.paths {
+            paths.push(PathRule {
+                matcher: compile_glob_set(std::slice::from_ref(&path.glob))?,
+                glob: path.glob,
+                rule: Rule::new(path.allow, path.deny)?,
+            });
+        }
+
+        let mut max_percentage = Vec::with_capacity(file.max_percentage.len());
+        for (name, max) in file.max_percentage {
+            max_percentage.push((resolve(&name)?, max));
+        }
+
+        let vendored_paths = match file.vendored_paths {
+            Some(globs) => Some(compile_glob_set(&globs)?),
+            None => None,
+        };
+
+        Ok(Policy {
+            global: Rule::new(file.allow, file.deny)?,
+            paths,
+            max_percentage,
+            vendored_paths,
+        })
+    }
+
+    /// Reads and parses a policy file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Policy::from_yaml(&fs::read_to_string(path)?)
+    }
+
+    /// Lists every way in which a breakdown breaks the policy: files first,
+    /// in path order, then languages above their maximum percentage.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use linguist::policy::Policy;
+    /// use linguist::repository::{ScanOptions, analyze};
+    ///
+    /// let policy = Policy::load(".linguist-policy.yml")?;
+    /// for violation in policy.check(&analyze(".", &ScanOptions::default())?) {
+    ///     println!("{violation}");
+    /// }
+    /// # Ok::<(), linguist::LinguistError>(())
+    /// ```
+    pub fn check(&self, breakdown: &Breakdown) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        for entry in &breakdown.files {
+            if entry.vendored {
+                if let Some(ref globs) = self.vendored_paths
+                    && !globs.is_match(&entry.path)
+                {
+                    violations.push(Violation::VendoredOutsideApprovedPaths {
+                        path: entry.path.clone(),
+                    });
+                }
+                continue;
+            }
+
+            let Some(language) = entry.language else {
+                continue;
+            };
+            if let Some(violation) = self.check_language(&entry.path, language) {
+                violations.push(violation);
+            }
+        }
+
+        let total = breakdown.total_bytes();
+        for &(language, max) in &self.max_percentage {
+            let bytes = breakdown
+                .languages
+                .get(language)
+                .map_or(0, |stats| stats.bytes);
+            let percentage = if total == 0 {
+                0.0
+            } else {
+                bytes as f64 * 100.0 / total as f64
+            };
+            if percentage > max {
+                violations.push(Violation::AboveMaxPercentage {
+                    language,
+                    percentage,
+                    max,
+                });
+            }
+        }
+
+        violations
+    }
+
+    /// Judges a file's language by the most specific rule deciding on it
+    fn check_language(&self, path: &Path, language: &'static str) -> Option<Violation> {
+        let restricted = is_code(language);
+        let path_rules = self.paths.iter().rev().filter(|p| p.matcher.is_match(path));
+        let rules = path_rules
+            .map(|p| (Some(&p.glob), &p.rule))
+            .chain([(None, &self.global)]);
+
+        for (glob, rule) in rules {
+            let rule_glob = glob.cloned();
+            if rule.deny.iter().any(|name| matches(name, language)) {
+                return Some(Violation::DeniedLanguage {
+                    path: path.to_path_buf(),
+                    language,
+                    rule: rule_glob,
+                });
+            }
+            if let Some(ref allow) = rule.allow
+                && restricted
+            {
+                if allow.iter().any(|name| matches(name, language)) {
+                    return None;
+                }
+                return Some(Violation::UnlistedLanguage {
+                    path: path.to_path_buf(),
+                    language,
+                    rule: rule_glob,
+                });
+            }
+        }
+        None
+    }
+}
+
+impl Rule {
+    fn new(allow: Option<Vec<String>>, deny: Vec<String>) -> Result<Self> {
+        let allow = match allow {
+            Some(names) => Some(names.iter().map(|n| resolve(n)).collect::<Result<_>>()?),
+            None => None,
+        };
+        let deny = deny.iter().map(|n| resolve(n)).collect::<Result<_>>()?;
+        Ok(Rule { allow, deny })
+    }
+}
+
+/// Resolves a language named in a policy to its canonical name
+fn resolve(name: &str) -> Result<&'static str> {
+    find_language(name)
+        .map(|language| language.name)
+        .ok_or_else(|| LinguistError::InvalidPolicy(format!("unknown language '{name}'")))
+}
+
+/// Whether a language is programming or markup, the only types `allow` lists
+/// restrict, as upstream only counts these in language statistics
+fn is_code(language: &str) -> bool {
+    definitions::LANGUAGES
+        .get(language)
+        .is_some_and(|definition| {
+            matches!(
+                definition.language_type,
+                LanguageType::Programming | LanguageType::Markup
+            )
+        })
+}
+
+/// Whether a listed language covers a file's language, directly or as its group
+fn matches(listed: &str, language: &str) -> bool {
+    listed == language
+        || definitions::LANGUAGES
+            .get(language)
+            .and_then(|definition| definition.group.as_deref())
+            == Some(listed)
+}
+
+/// Compiles globs matched against paths relative to the repository root. An
+/// empty list matches nothing.
+fn compile_glob_set(globs: &[String]) -> Result<GlobSet> {
+    compile_globs(globs)
+        .map(|set| set.unwrap_or_else(GlobSet::empty))
+        .map_err(|e| LinguistError::InvalidPolicy(e.to_string()))
+}
@@ -0,0 +1,651 @@
+//! Results of the command-line interface, printed as text or JSON.
+//!
+//! The JSON form of these reports is shared by `--format json` and the
+//! `serve` HTTP API, so both always return the same schema.
+
+use crate::cli::{DetectionMethods, Format};
+use linguist::diff::BreakdownDiff;
+use linguist::gitattributes::GitAttributes;
+use linguist::lines::LineCounts;
+use linguist::notebook::{NOTEBOOK_LANGUAGE, NotebookInfo, inspect_notebook};
+use linguist::policy::Violation;
+use linguist::repository::Breakdown;
+use linguist::{
+    DetectedLanguage, HeuristicLimits, LinguistError, Result, detect_language_by_extension,
+    detect_language_by_filename, disambiguate_with_limits, utils, vendored_pattern,
+};
+use linguist_types::{Language, LanguageType};
+use serde::Serialize;
+use std::io::Read;
+
+/// How a set of candidate languages was found.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Method {
+    Extension,
+    Filename,
+    Content,
+
+    /// The kernel language of a Jupyter notebook
+    Embedded,
+}
+
+/// Languages found by one detection method.
+#[derive(Serialize, Debug)]
+pub struct Detection {
+    pub method: Method,
+    pub languages: Vec<&'static str>,
+}
+
+/// Detection results for a single file.
+#[derive(Serialize, Debug)]
+pub struct FileReport {
+    pub path: String,
+    pub vendored: bool,
+
+    /// The vendor.yml pattern making the file vendored, unless it isn't
+    /// vendored or the configuration decided it is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendored_pattern: Option<&'static str>,
+
+    /// One entry per method that found any languages; empty if unknown
+    pub detections: Vec<Detection>,
+
+    /// Methods that failed, which doesn't stop the others
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Runs the selected detection methods on a file.
+///
+/// `open` is called for each method that needs the file's content, so it can
+/// return a fresh reader over the same bytes every time. Heuristics exceeding
+/// `limits` don't match, and are reported as warnings.
+pub fn detect_file<R, F>(
+    path: &str,
+    methods: DetectionMethods,
+    read_limit: usize,
+    limits: &HeuristicLimits,
+    open: F,
+) -> FileReport
+where
+    R: Read,
+    F: Fn() -> Result<R>,
+{
+    let mut report = FileReport {
+        path: path.to_string(),
+        vendored: false,
+        vendored_pattern: None,
+        detections: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    // Paths are relative to the current directory, taken as the repository
+    // root, and absolute ones outside it to the filesystem root
+    let pattern = match vendored_pattern(".", path) {
+        Err(LinguistError::InvalidPath(_)) => vendored_pattern("/", path),
+        result => result,
+    };
+    match pattern {
+        Ok(pattern) => {
+            report.vendored = pattern.is_some();
+            report.vendored_pattern = pattern;
+        }
+        Err(LinguistError::InvalidPath(_)) => {} // Going up with "..", so in no repository
+        Err(e) => report
+            .warnings
+            .push(format!("Error checking whether {path} is vendored: {e}")),
+    }
+
+    if methods.by_extension {
+        match detect_language_by_extension(path) {
+            Ok(languages) => report.add(Method::Extension, languages),
+            Err(e) => report
+                .warnings
+                .push(format!("Error detecting by extension for {path}: {e}")),
+        }
+    }
+
+    if methods.by_filename {
+        match detect_language_by_filename(path) {
+            Ok(languages) => report.add(Method::Filename, languages),
+            Err(e) => report
+                .warnings
+                .push(format!("Error detecting by filename for {path}: {e}")),
+        }
+    }
+
+    // Only ever read a bounded prefix of the content
+    if methods.by_content {
+        match open().and_then(|reader| utils::read_prefix(reader, read_limit)) {
+            Ok(content) => match disambiguate_with_limits(path, &content, limits) {
+                Ok(outcome) => {
+                    report.warnings.extend(
+                        outcome
+                            .warnings
+                            .iter()
+                            .map(|e| format!("Gave up on a heuristic for {path}: {e}")),
+                    );
+                    report.add(Method::Content, outcome.languages);
+                }
+                Err(e) => report
+                    .warnings
+                    .push(format!("Error during disambiguation for {path}: {e}")),
+            },
+            Err(e) => report
+                .warnings
+                .push(format!("Failed to read file {path}: {e}")),
+        }
+    }
+
+    // Notebooks record the language of their code cells in their metadata
+    if methods.by_content && is_notebook(path) {
+        match open().and_then(inspect_notebook) {
+            Ok(NotebookInfo {
+                language: Some(language),
+                ..
+            }) => report.add(Method::Embedded, vec![language]),
+            Ok(_) => {} // No kernel language, that's ok
+            Err(e) => report
+                .warnings
+                .push(format!("Failed to inspect notebook {path}: {e}")),
+        }
+    }
+
+    report
+}
+
+/// Whether a path is detected as a Jupyter notebook by its extension
+pub fn is_notebook(path: &str) -> bool {
+    detect_language_by_extension(path)
+        .map(|languages| languages.iter().any(|l| l.name == NOTEBOOK_LANGUAGE))
+        .unwrap_or(false)
+}
+
+impl FileReport {
+    fn add(&mut self, method: Method, languages: Vec<DetectedLanguage>) {
+        if !languages.is_empty() {
+            self.detections.push(Detection {
+                method,
+                languages: languages.iter().map(|l| l.name).collect(),
+            });
+        }
+    }
+
+    /// Applies configured overrides: the vendored flag, and a language forced
+    /// on the file's extension in place of the one detected by extension.
+    pub fn apply_overrides(&mut self, overrides: &GitAttributes, methods: DetectionMethods) {
+        if let Some(vendored) = overrides.vendored(&self.path) {
+            self.vendored = vendored;
+            self.vendored_pattern = None;
+        }
+        let Some(language) = overrides.language(&self.path) else {
+            return;
+        };
+        if !methods.by_extension {
+            return;
+        }
+        self.detections
+            .retain(|detection| detection.method != Method::Extension);
+        self.detections.insert(
+            0,
+            Detection {
+                method: Method::Extension,
+                languages: vec![language],
+            },
+        );
+    }
+
+    /// Prints why the file is or isn't vendored, for `--explain`.
+    pub fn print_explanation(&self) {
+        match (self.vendored, self.vendored_pattern) {
+            (true, Some(pattern)) => {
+                println!(
+                    "{}: vendored, matches vendor.yml pattern {pattern}",
+                    self.path
+                )
+            }
+            (true, None) => println!("{}: vendored by the configuration", self.path),
+            (false, _) => println!("{}: not vendored", self.path),
+        }
+    }
+
+    /// Prints the report in the original one-line-per-method text format,
+    /// with warnings on stderr.
+    pub fn print_text(&self) {
+        for warning in &self.warnings {
+            eprintln!("Warning: {warning}");
+        }
+
+        let vendored_status = if self.vendored { "[vendored]" } else { "" };
+        for detection in &self.detections {
+            let method = match detection.method {
+                Method::Extension => "by extension".to_string(),
+                Method::Filename => "by filename".to_string(),
+                Method::Content => "by content".to_string(),
+                Method::Embedded => format!("embedded in {NOTEBOOK_LANGUAGE}"),
+            };
+            println!(
+                "{}: {} ({}) {}",
+                self.path,
+                detection.languages.join(", "),
+                method,
+                vendored_status
+            );
+        }
+
+        // If no language was detected by any method, report as unknown
+        if self.detections.is_empty() {
+            println!("{}: Unknown {}", self.path, vendored_status);
+        }
+    }
+}
+
+/// Prints file reports as soon as they are produced, so long file lists
+/// neither delay output nor hold every report in memory.
+///
+/// JSON output is one list, printed exactly as `to_json` would print it.
+pub struct FileReportPrinter {
+    format: Format,
+    explain: bool,
+    printed: usize,
+}
+
+impl FileReportPrinter {
+    /// `explain` adds why each file is or isn't vendored to text output; JSON
+    /// output always has the matching vendor pattern.
+    pub fn new(format: Format, explain: bool) -> Self {
+        FileReportPrinter {
+            format,
+            explain,
+            printed: 0,
+        }
+    }
+
+    pub fn print(&mut self, report: &FileReport) {
+        match self.format {
+            Format::Text => {
+                report.print_text();
+                if self.explain {
+                    report.print_explanation();
+                }
+            }
+            Format::Json => {
+                let separator = if self.printed == 0 { "[" } else { "," };
+                let indented = to_json(report).replace('\n', "\n  ");
+                println!("{separator}\n  {indented}");
+            }
+        }
+        self.printed += 1;
+    }
+
+    /// Closes the JSON list.
+    pub fn finish(self) {
+        if self.format == Format::Json {
+            println!("{}", if self.printed == 0 { "[]" } else { "]" });
+        }
+    }
+}
+
+/// Totals of one language in a breakdown.
+#[derive(Serialize, Debug)]
+pub struct LanguageTotals {
+    pub name: &'static str,
+    pub bytes: u64,
+    pub files: usize,
+
+    /// Share of all bytes in the breakdown, from 0 to 100
+    pub percentage: f64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<LineCounts>,
+}
+
+/// A file that couldn't be classified.
+#[derive(Serialize, Debug)]
+pub struct FileError {
+    pub path: String,
+    pub error: String,
+}
+
+/// A vendored or documentation directory skipped by a scan.
+#[derive(Serialize, Debug)]
+pub struct PrunedDirReport {
+    pub path: String,
+    pub pattern: &'static str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+}
+
+/// The language breakdown of a directory, revision or archive.
+#[derive(Serialize, Debug)]
+pub struct BreakdownReport {
+    pub total_bytes: u64,
+
+    /// Languages sorted by size, largest first
+    pub languages: Vec<LanguageTotals>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FileError>,
+
+    /// Vendored and documentation directories whose files weren't classified
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pruned: Vec<PrunedDirReport>,
+}
+
+impl BreakdownReport {
+    /// Summarizes a breakdown, including line counts if they were counted.
+    pub fn new(breakdown: &Breakdown, count_lines: bool) -> Self {
+        let total_bytes = breakdown.total_bytes();
+        let languages = breakdown
+            .sorted_languages()
+            .into_iter()
+            .map(|(name, stats)| LanguageTotals {
+                name,
+                bytes: stats.bytes,
+                files: stats.files,
+                percentage: if total_bytes > 0 {
+                    stats.bytes as f64 * 100.0 / total_bytes as f64
+                } else {
+                    0.0
+                },
+                lines: count_lines.then_some(stats.lines),
+            })
+            .collect();
+        let errors = breakdown
+            .errors
+            .iter()
+            .map(|(path, error)| FileError {
+                path: path.display().to_string(),
+                error: error.to_string(),
+            })
+            .collect();
+
+        let pruned = breakdown
+            .pruned
+            .iter()
+            .map(|dir| PrunedDirReport {
+                path: dir.path.display().to_string(),
+                pattern: dir.pattern,
+                bytes: dir.bytes,
+            })
+            .collect();
+
+        BreakdownReport {
+            total_bytes,
+            languages,
+            errors,
+            pruned,
+        }
+    }
+
+    /// Prints the breakdown as a table, with errors on stderr.
+    pub fn print_text(&self, count_lines: bool) {
+        for error in &self.errors {
+            eprintln!(
+                "Warning: Failed to classify {}: {}",
+                error.path, error.error
+            );
+        }
+
+        if count_lines {
+            println!(
+                "{:>7}  {:>12}  {:>7}  {:>9}  {:>9}  {:>9}  LANGUAGE",
+                "PERCENT", "BYTES", "FILES", "CODE", "COMMENT", "BLANK"
+            );
+        }
+
+        for language in &self.languages {
+            match language.lines {
+                Some(lines) => println!(
+                    "{:>6.2}%  {:>12}  {:>7}  {:>9}  {:>9}  {:>9}  {}",
+                    language.percentage,
+                    language.bytes,
+                    language.files,
+                    lines.code,
+                    lines.comment,
+                    lines.blank,
+                    language.name
+                ),
+                None => println!(
+                    "{:>6.2}%  {:>12}  {:>7}  {}",
+                    language.percentage, language.bytes, language.files, language.name
+                ),
+            }
+        }
+
+        // Sizes are only known if they were counted
+        let pruned_bytes: Option<u64> = self.pruned.iter().map(|dir| dir.bytes).sum();
+        if let Some(bytes) = pruned_bytes
+            && !self.pruned.is_empty()
+        {
+            let dirs = if self.pruned.len() == 1 {
+                "directory"
+            } else {
+                "directories"
+            };
+            println!(
+                "Skipped {} vendored or documentation {dirs} ({bytes} bytes)",
+                self.pruned.len()
+            );
+        }
+    }
+}
+
+/// Change of one language between two breakdowns.
+#[derive(Serialize, Debug)]
+pub struct LanguageChangeReport {
+    pub name: &'static str,
+    pub bytes: i64,
+    pub files: i64,
+}
+
+/// A file whose detected language changed.
+#[derive(Serialize, Debug)]
+pub struct FileChangeReport {
+    pub path: String,
+    pub before: Option<&'static str>,
+    pub after: Option<&'static str>,
+}
+
+/// The difference between two breakdowns.
+#[derive(Serialize, Debug)]
+pub struct DiffReport {
+    /// Languages sorted by the size of their change, largest first
+    pub languages: Vec<LanguageChangeReport>,
+    pub changed_files: Vec<FileChangeReport>,
+}
+
+impl DiffReport {
+    pub fn new(changes: &BreakdownDiff) -> Self {
+        DiffReport {
+            languages: changes
+                .sorted_languages()
+                .into_iter()
+                .map(|(name, delta)| LanguageChangeReport {
+                    name,
+                    bytes: delta.bytes,
+                    files: delta.files,
+                })
+                .collect(),
+            changed_files: changes
+                .changed_files
+                .iter()
+                .map(|change| FileChangeReport {
+                    path: change.path.display().to_string(),
+                    before: change.before,
+                    after: change.after,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn print_text(&self) {
+        for language in &self.languages {
+            println!(
+                "{:>+12} bytes  {:>+7} files  {}",
+                language.bytes, language.files, language.name
+            );
+        }
+
+        if !self.changed_files.is_empty() {
+            println!();
+            println!("Language changes:");
+            for change in &self.changed_files {
+                println!(
+                    "  {}: {} -> {}",
+                    change.path,
+                    change.before.unwrap_or("Unknown"),
+                    change.after.unwrap_or("Unknown")
+                );
+            }
+        }
+    }
+}
+
+/// One way in which a policy is broken, for JSON output.
+#[derive(Serialize, Debug)]
+pub struct ViolationReport {
+    /// The offending file, unless a language's total share is too large
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    pub message: String,
+}
+
+/// The result of `check`.
+#[derive(Serialize, Debug)]
+pub struct CheckReport {
+    pub passed: bool,
+    pub violations: Vec<ViolationReport>,
+}
+
+impl CheckReport {
+    pub fn new(violations: &[Violation]) -> Self {
+        CheckReport {
+            passed: violations.is_empty(),
+            violations: violations
+                .iter()
+                .map(|violation| ViolationReport {
+                    path: violation
+                        .path()
+                        .map(|path| path.to_string_lossy().into_owned()),
+                    message: violation.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn print_text(&self) {
+        if self.passed {
+            println!("No policy violations");
+            return;
+        }
+
+        println!("Policy violations:");
+        for violation in &self.violations {
+            println!("  {}", violation.message);
+        }
+        let count = self.violations.len();
+        println!("{} violation{}", count, if count == 1 { "" } else { "s" });
+    }
+}
+
+/// A file listed by `ls`, with the language it was detected as.
+#[derive(Serialize, Debug)]
+pub struct ListedFile {
+    pub path: String,
+    pub language: &'static str,
+}
+
+/// The full definition of a language.
+#[derive(Serialize, Debug)]
+pub struct LanguageReport {
+    pub name: &'static str,
+
+    #[serde(flatten)]
+    pub definition: &'static Language,
+}
+
+impl From<DetectedLanguage> for LanguageReport {
+    fn from(language: DetectedLanguage) -> Self {
+        LanguageReport {
+            name: language.name,
+            definition: language.definition,
+        }
+    }
+}
+
+impl LanguageReport {
+    /// Prints every field of the definition that is set, one per line.
+    pub fn print_text(&self) {
+        let definition = self.definition;
+        let lists = [
+            ("Aliases", &definition.aliases),
+            ("Extensions", &definition.extensions),
+            ("Filenames", &definition.filenames),
+            ("Interpreters", &definition.interpreters),
+        ];
+        let optional = [
+            ("Color", &definition.color),
+            ("Group", &definition.group),
+            ("CodeMirror mode", &definition.codemirror_mode),
+            ("CodeMirror MIME type", &definition.codemirror_mime_type),
+            ("Filesystem name", &definition.fs_name),
+        ];
+
+        println!("{:<21} {}", "Name:", self.name);
+        println!("{:<21} {}", "Type:", type_name(&definition.language_type));
+        for (label, values) in lists {
+            if let Some(values) = values {
+                println!("{:<21} {}", format!("{label}:"), values.join(", "));
+            }
+        }
+        for (label, value) in optional {
+            if let Some(value) = value {
+                println!("{:<21} {}", format!("{label}:"), value);
+            }
+        }
+        println!("{:<21} {}", "TextMate scope:", definition.tm_scope);
+        println!("{:<21} {}", "Ace mode:", definition.ace_mode);
+        if let Some(wrap) = definition.wrap {
+            println!("{:<21} {}", "Wrap:", wrap);
+        }
+        println!("{:<21} {}", "Language ID:", definition.language_id);
+    }
+}
+
+/// The name of a language type, as written in languages.yml
+fn type_name(language_type: &LanguageType) -> &'static str {
+    match language_type {
+        LanguageType::Data => "data",
+        LanguageType::Programming => "programming",
+        LanguageType::Markup => "markup",
+        LanguageType::Prose => "prose",
+    }
+}
+
+/// Prints a list of languages as a table of names and types.
+pub fn print_language_list(languages: &[LanguageReport]) {
+    let width = languages.iter().map(|l| l.name.len()).max().unwrap_or(0);
+    for language in languages {
+        println!(
+            "{:<width$}  {}",
+            language.name,
+            type_name(&language.definition.language_type)
+        );
+    }
+}
+
+/// Serializes a report as pretty-printed JSON
+pub fn to_json<T: Serialize>(report: &T) -> String {
+    // Reports only hold strings, numbers and lists, which always serialize
+    serde_json::to_string_pretty(report).expect("Reports always serialize to JSON")
+}
+
+/// Error of a failed request, for JSON output.
+#[derive(Serialize, Debug)]
+pub struct ErrorReport {
+    pub error: String,
+}
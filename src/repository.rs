@@ -0,0 +1,751 @@
+//! Repository scanning: walking a directory tree and building a language breakdown.
+//!
+//! Files are classified on a pool of worker threads. The definitions in
+//! `definitions` and `indexed` are immutable once loaded, so workers share them
+//! freely; results are always returned in path order regardless of how the
+//! work was scheduled.
+
+use crate::archive;
+use crate::cache::Cache;
+use crate::embedded::{find_regions, has_regions};
+use crate::generated::{MARKER_LEN, is_generated};
+use crate::gitattributes::GitAttributes;
+use crate::lines::{LineCounts, count_lines};
+use crate::notebook::{NOTEBOOK_LANGUAGE, NotebookInfo, inspect_notebook};
+use crate::strategy::Pipeline;
+use crate::{
+    DEFAULT_READ_LIMIT, HeuristicLimits, LinguistError, Result, indexed, is_documentation,
+    is_vendored,
+};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::collections::BTreeMap;
+use std::fs::{self, File, Metadata};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Options controlling how a repository is scanned.
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Number of worker threads used to classify files
+    pub jobs: usize,
+
+    /// Maximum number of bytes read from each file for content analysis
+    pub read_limit: usize,
+
+    /// Limits on the heuristics run on that content. Rules exceeding them are
+    /// taken as not matching.
+    pub heuristic_limits: HeuristicLimits,
+
+    /// Detection pipeline with custom strategies to classify files with. The
+    /// built-in pipeline is used when unset, and `heuristic_limits` only apply
+    /// to the latter.
+    pub pipeline: Option<Arc<Pipeline>>,
+
+    /// Directory of an on-disk cache of detection results. When set, files
+    /// whose size and modification time are unchanged since the last scan
+    /// aren't classified again.
+    pub cache_dir: Option<PathBuf>,
+
+    /// Whether to count code, comment and blank lines of every file with a
+    /// language. Unlike detection, this reads files in full.
+    pub count_lines: bool,
+
+    /// Whether to look for code in other languages inside files, such as the
+    /// code cells of Jupyter notebooks or the `<script>` blocks of HTML and Vue
+    /// files, and attribute those bytes to the embedded language. Like line
+    /// counting, this reads files in full.
+    pub embedded_languages: bool,
+
+    /// Whether to also read the first few KB of every file for markers of
+    /// generated code, such as `DO NOT EDIT`, when setting
+    /// [`FileEntry::generated`]. Names such as `Cargo.lock` are always
+    /// checked, but this reads files that detection could tell by name alone.
+    pub generated_markers: bool,
+
+    /// Which files of the tree are scanned; all of them by default
+    pub filter: PathFilter,
+
+    /// Overrides applied as if from a `.gitattributes` file above the root,
+    /// e.g. from a project configuration. The tree's own `.gitattributes`
+    /// files take precedence over them.
+    pub overrides: GitAttributes,
+
+    /// Whether to skip directories matching a vendor or documentation
+    /// pattern, such as `node_modules/` or `docs/`, rather than classify every
+    /// file below them as vendored or documentation. They are listed in
+    /// [`Breakdown::pruned`] instead, so [`Breakdown::files`] lacks their
+    /// files. Only applies when walking a directory, and not when
+    /// `.gitattributes` files mark any path as not vendored.
+    pub prune_vendored: bool,
+
+    /// Whether to add up the sizes of the files in pruned directories, which
+    /// still lists them but is much cheaper than classifying them
+    pub count_pruned_bytes: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            jobs: default_jobs(),
+            read_limit: DEFAULT_READ_LIMIT,
+            heuristic_limits: HeuristicLimits::default(),
+            pipeline: None,
+            cache_dir: None,
+            count_lines: false,
+            embedded_languages: false,
+            generated_markers: false,
+            filter: PathFilter::default(),
+            overrides: GitAttributes::new(),
+            prune_vendored: false,
+            count_pruned_bytes: false,
+        }
+    }
+}
+
+/// Include and exclude globs selecting the files of a tree to scan.
+///
+/// Globs follow gitattributes rules: one without a slash matches a file name
+/// at any depth, otherwise it is matched against the path relative to the
+/// root, and one ending in a slash matches everything below a directory. A
+/// path is scanned if it matches any include glob (or there are none) and no
+/// exclude glob.
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl PathFilter {
+    /// Compiles include and exclude globs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use linguist::repository::PathFilter;
+    ///
+    /// let filter = PathFilter::new(&["src/", "*.py"], &["*.min.js", "src/generated/"])?;
+    /// assert!(filter.matches("src/app.js"));
+    /// assert!(filter.matches("tools/build.py"));
+    /// assert!(!filter.matches("src/app.min.js"));
+    /// assert!(!filter.matches("src/generated/schema.rs"));
+    /// assert!(!filter.matches("docs/index.md"));
+    /// # Ok::<(), linguist::LinguistError>(())
+    /// ```
+    pub fn new<S: AsRef<str>>(include: &[S], exclude: &[S]) -> Result<Self> {
+        Ok(PathFilter {
+            include: compile_globs(include)?,
+            exclude: compile_globs(exclude)?,
+        })
+    }
+
+    /// Whether a path relative to the root is scanned.
+    pub fn matches<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = path.as_ref();
+        self.include.as_ref().is_none_or(|set| set.is_match(path))
+            && !self.exclude.as_ref().is_some_and(|set| set.is_match(path))
+    }
+}
+
+/// Compiles globs following the rules of [`PathFilter`], `None` if there are
+/// none
+pub(crate) fn compile_globs<S: AsRef<str>>(globs: &[S]) -> Result<Option<GlobSet>> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in globs {
+        let pattern = pattern.as_ref();
+        let invalid = |error: String| LinguistError::InvalidGlob {
+            glob: pattern.to_string(),
+            error,
+        };
+
+        let trimmed = pattern.trim_end_matches('/');
+        if trimmed.trim_start_matches('/').is_empty() {
+            return Err(invalid("empty pattern".to_string()));
+        }
+
+        // Patterns without a slash match at any depth, others are anchored
+        let mut glob = match trimmed.strip_prefix('/') {
+            Some(anchored) => anchored.to_string(),
+            None if trimmed.contains('/') => trimmed.to_string(),
+            None => format!("**/{trimmed}"),
+        };
+        if pattern.ends_with('/') {
+            glob.push_str("/**");
+        }
+
+        let glob = GlobBuilder::new(&glob)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| invalid(e.to_string()))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| LinguistError::InvalidGlob {
+            glob: globs
+                .iter()
+                .map(AsRef::as_ref)
+                .collect::<Vec<_>>()
+                .join(", "),
+            error: e.to_string(),
+        })
+}
+
+/// Returns the number of worker threads used when none is specified.
+///
+/// This is the available parallelism of the machine, or 1 if it can't be determined.
+pub fn default_jobs() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// The classification of a single file in a repository.
+#[derive(Debug, Clone)]
+pub struct FileEntry {
+    /// Path of the file relative to the repository root
+    pub path: PathBuf,
+
+    /// Size of the file in bytes
+    pub size: u64,
+
+    /// The detected language, if any. When detection remains ambiguous the
+    /// first candidate is used.
+    pub language: Option<&'static str>,
+
+    /// Whether the file is vendored (and therefore excluded from the breakdown)
+    pub vendored: bool,
+
+    /// Whether the file is documentation, such as a README or a file below
+    /// `docs/` (also excluded from the breakdown)
+    pub documentation: bool,
+
+    /// Whether the file is generated, such as a lock file or, if
+    /// [`ScanOptions::generated_markers`] was set, code marked `DO NOT EDIT`
+    /// (see [`crate::generated`]). Generated files still count towards the
+    /// breakdown, but `linguist ls` leaves them out.
+    pub generated: bool,
+
+    /// Code, comment and blank line counts, if [`ScanOptions::count_lines`]
+    /// was set and the file has a language
+    pub lines: Option<LineCounts>,
+
+    /// Bytes of the file written in other languages, if
+    /// [`ScanOptions::embedded_languages`] was set. For a Jupyter notebook this
+    /// is its kernel language; see [`crate::embedded`] for the other files split
+    /// into regions.
+    pub embedded: Vec<EmbeddedBytes>,
+}
+
+/// Part of a file attributed to a language other than the file's own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddedBytes {
+    /// The embedded language
+    pub language: &'static str,
+
+    /// Number of bytes written in that language
+    pub bytes: u64,
+}
+
+/// Aggregated statistics for a single language.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LanguageStats {
+    /// Total size in bytes of all files of this language
+    pub bytes: u64,
+
+    /// Number of files of this language
+    pub files: usize,
+
+    /// Line counts of all files of this language (zero unless lines were counted)
+    pub lines: LineCounts,
+}
+
+/// The language breakdown of a repository.
+#[derive(Debug, Clone, Default)]
+pub struct Breakdown {
+    /// Every file that was classified, in path order
+    pub files: Vec<FileEntry>,
+
+    /// Per-language totals, excluding vendored and documentation files and files
+    /// with no language
+    pub languages: BTreeMap<&'static str, LanguageStats>,
+
+    /// Files that could not be classified, with the reason why
+    pub errors: Vec<(PathBuf, LinguistError)>,
+
+    /// Vendored and documentation directories skipped by
+    /// [`ScanOptions::prune_vendored`], in path order
+    pub pruned: Vec<PrunedDir>,
+}
+
+/// A vendored or documentation directory whose files weren't classified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrunedDir {
+    /// Path of the directory relative to the repository root
+    pub path: PathBuf,
+
+    /// The vendor or documentation pattern matching the directory
+    pub pattern: &'static str,
+
+    /// Total size in bytes of the files below the directory, if
+    /// [`ScanOptions::count_pruned_bytes`] was set
+    pub bytes: Option<u64>,
+}
+
+impl Breakdown {
+    /// Builds a breakdown from classified files, in the order given.
+    pub fn from_entries(entries: Vec<FileEntry>) -> Self {
+        let mut breakdown = Breakdown::default();
+        for entry in entries {
+            breakdown.add(entry);
+        }
+        breakdown
+    }
+
+    /// Adds a classified file to the breakdown, updating the language totals.
+    ///
+    /// Embedded bytes count towards their own language; the file (and its
+    /// remaining bytes) towards the file's language.
+    pub fn add(&mut self, entry: FileEntry) {
+        if !entry.vendored
+            && !entry.documentation
+            && let Some(language) = entry.language
+        {
+            let embedded: u64 = entry.embedded.iter().map(|e| e.bytes).sum();
+            let stats = self.languages.entry(language).or_default();
+            stats.bytes += entry.size.saturating_sub(embedded);
+            stats.files += 1;
+            if let Some(lines) = entry.lines {
+                stats.lines += lines;
+            }
+
+            for embedded in &entry.embedded {
+                self.languages.entry(embedded.language).or_default().bytes += embedded.bytes;
+            }
+        }
+        self.files.push(entry);
+    }
+
+    /// Total size in bytes of all languages in the breakdown.
+    pub fn total_bytes(&self) -> u64 {
+        self.languages.values().map(|stats| stats.bytes).sum()
+    }
+
+    /// Languages sorted by size, largest first (ties broken by name).
+    pub fn sorted_languages(&self) -> Vec<(&'static str, LanguageStats)> {
+        let mut languages: Vec<_> = self
+            .languages
+            .iter()
+            .map(|(name, stats)| (*name, *stats))
+            .collect();
+        languages.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then(a.0.cmp(b.0)));
+        languages
+    }
+}
+
+/// Recursively lists the files under `root`, as paths relative to `root`.
+///
+/// The `.git` directory is skipped and symbolic links are not followed. Paths
+/// are returned in sorted order. If `root` is a zip or tar archive (see
+/// [`archive::is_archive`]), its entries are listed instead.
+///
+/// # Examples
+///
+/// ```no_run
+/// use linguist::repository::walk;
+///
+/// for path in walk(".")? {
+///     println!("{}", path.display());
+/// }
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn walk<P: AsRef<Path>>(root: P) -> Result<Vec<PathBuf>> {
+    let root = root.as_ref();
+    if is_archive_file(root) {
+        return archive::walk_archive(root);
+    }
+
+    let mut files = Vec::new();
+    walk_dir(root, Path::new(""), &mut files, None)?;
+    files.sort();
+    Ok(files)
+}
+
+/// Whether `root` is an archive file rather than a directory to walk
+fn is_archive_file(root: &Path) -> bool {
+    archive::is_archive(root) && root.is_file()
+}
+
+/// Lists the files under `root` like [`walk`], but skips the directories
+/// matching a vendor or documentation pattern, which are returned separately
+fn walk_pruned(root: &Path, options: &ScanOptions) -> Result<(Vec<PathBuf>, Vec<PrunedDir>)> {
+    let mut files = Vec::new();
+    let mut pruned = Vec::new();
+    walk_dir(root, Path::new(""), &mut files, Some(&mut pruned))?;
+    files.sort();
+    pruned.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if options.count_pruned_bytes {
+        for dir in &mut pruned {
+            let mut below = Vec::new();
+            walk_dir(root, &dir.path, &mut below, None)?;
+            let mut bytes = 0;
+            for path in below.iter().filter(|path| options.filter.matches(path)) {
+                bytes += fs::symlink_metadata(root.join(path))?.len();
+            }
+            dir.bytes = Some(bytes);
+        }
+    }
+    Ok((files, pruned))
+}
+
+/// The vendor or documentation pattern matching a directory, if every path
+/// below it matches too. Only patterns ending with a `/`, like
+/// `(^|/)node_modules/`, are known to: others, like `(^|/)configure$` or
+/// `(^|/)README(\.|$)`, are left to match the files themselves.
+fn pruned_dir(relative: &Path) -> Result<Option<&'static str>> {
+    let Some(path) = relative.to_str() else {
+        return Ok(None); // Its files fail to classify, and are reported then
+    };
+    let dir = format!("{}/", path.replace('\\', "/"));
+    for patterns in [&indexed::VENDOR_PATTERNS, &indexed::DOCUMENTATION_PATTERNS] {
+        if let Some(pattern) = patterns.first_match(&dir)?
+            && pattern.ends_with('/')
+        {
+            return Ok(Some(pattern));
+        }
+    }
+    Ok(None)
+}
+
+fn walk_dir(
+    root: &Path,
+    relative: &Path,
+    files: &mut Vec<PathBuf>,
+    mut pruned: Option<&mut Vec<PrunedDir>>,
+) -> Result<()> {
+    for entry in fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = relative.join(entry.file_name());
+
+        if file_type.is_dir() {
+            if entry.file_name() == ".git" {
+                continue;
+            }
+            if let Some(ref mut pruned) = pruned
+                && let Some(pattern) = pruned_dir(&path)?
+            {
+                pruned.push(PrunedDir {
+                    path,
+                    pattern,
+                    bytes: None,
+                });
+                continue;
+            }
+            walk_dir(root, &path, files, pruned.as_deref_mut())?;
+        } else if file_type.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Classifies a single file, given the repository root and its relative path.
+pub fn classify_file(root: &Path, relative: &Path, options: &ScanOptions) -> Result<FileEntry> {
+    let file = File::open(root.join(relative))?;
+    let size = file.metadata()?.len();
+    classify_reader(relative, size, file, options)
+}
+
+/// Classifies a file whose content comes from a reader rather than the
+/// filesystem, e.g. a blob in a git object database.
+///
+/// `relative` is the path of the file relative to the repository root and
+/// `size` its full size in bytes. Content is only read when detection needs it,
+/// or [`ScanOptions::generated_markers`] is set, and never more than
+/// `options.read_limit` bytes.
+pub fn classify_reader<R: Read>(
+    relative: &Path,
+    size: u64,
+    mut reader: R,
+    options: &ScanOptions,
+) -> Result<FileEntry> {
+    let mut head = Vec::new();
+    if options.generated_markers {
+        reader
+            .by_ref()
+            .take(MARKER_LEN.min(options.read_limit) as u64)
+            .read_to_end(&mut head)?;
+    }
+    let generated = is_generated(relative, &head);
+    let reader = head.as_slice().chain(reader);
+
+    let languages = match options.pipeline {
+        Some(ref pipeline) => pipeline.classify(relative, reader, options.read_limit)?,
+        None => Pipeline::with_heuristic_limits(options.heuristic_limits).classify(
+            relative,
+            reader,
+            options.read_limit,
+        )?,
+    };
+
+    Ok(FileEntry {
+        path: relative.to_path_buf(),
+        size,
+        language: languages.first().map(|lang| lang.name),
+        vendored: is_vendored(relative)?,
+        documentation: is_documentation(relative)?,
+        generated,
+        lines: None,
+        embedded: Vec::new(),
+    })
+}
+
+/// Whether files of a language can have other languages embedded in them
+pub(crate) fn may_embed(language: &str) -> bool {
+    language == NOTEBOOK_LANGUAGE || has_regions(language)
+}
+
+/// Finds the parts of a file in `language` written in other languages.
+///
+/// Content that doesn't parse as expected (e.g. a corrupt notebook, or a
+/// container file that isn't UTF-8) simply has nothing embedded.
+pub(crate) fn find_embedded<R: Read>(language: &str, mut reader: R) -> Result<Vec<EmbeddedBytes>> {
+    if has_regions(language) {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        let Ok(content) = String::from_utf8(content) else {
+            return Ok(Vec::new());
+        };
+
+        // One entry per language, in name order
+        let mut bytes: BTreeMap<&'static str, u64> = BTreeMap::new();
+        for region in find_regions(language, &content) {
+            *bytes.entry(region.language).or_default() += region.range.len() as u64;
+        }
+        return Ok(bytes
+            .into_iter()
+            .map(|(language, bytes)| EmbeddedBytes { language, bytes })
+            .collect());
+    }
+
+    if language != NOTEBOOK_LANGUAGE {
+        return Ok(Vec::new());
+    }
+
+    match inspect_notebook(reader) {
+        Ok(NotebookInfo {
+            language: Some(kernel),
+            code_bytes,
+        }) => Ok(vec![EmbeddedBytes {
+            language: kernel.name,
+            bytes: code_bytes,
+        }]),
+        Ok(_) | Err(LinguistError::InvalidNotebook(_)) => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Classifies a list of files relative to `root` across `options.jobs` threads.
+///
+/// Results are returned in the same order as `paths`.
+pub fn classify_files(
+    root: &Path,
+    paths: &[PathBuf],
+    options: &ScanOptions,
+) -> Vec<Result<FileEntry>> {
+    parallel_map(paths, options.jobs, |path| {
+        classify_file(root, path, options)
+    })
+}
+
+/// Applies `f` to every item across `jobs` threads, returning results in the
+/// same order as `items`.
+pub(crate) fn parallel_map<T, R, F>(items: &[T], jobs: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let jobs = jobs.clamp(1, items.len().max(1));
+    let next = AtomicUsize::new(0);
+
+    // Each worker pulls the next unprocessed index until none remain, then
+    // hands back its (index, result) pairs to be put back in order
+    //
+    let mut indexed: Vec<(usize, R)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            break;
+                        };
+                        results.push((index, f(item)));
+                    }
+                    results
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("classification worker panicked"))
+            .collect()
+    });
+
+    indexed.sort_by_key(|(index, _)| *index);
+    indexed.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Reads every `.gitattributes` file among `paths`, shallowest first, on top
+/// of the given overrides.
+fn load_gitattributes(
+    root: &Path,
+    paths: &[PathBuf],
+    overrides: &GitAttributes,
+) -> Result<GitAttributes> {
+    let mut files: Vec<&PathBuf> = paths
+        .iter()
+        .filter(|path| path.file_name() == Some(".gitattributes".as_ref()))
+        .collect();
+    files.sort_by_key(|path| path.components().count());
+
+    let mut attributes = overrides.clone();
+    for path in files {
+        let content = fs::read_to_string(root.join(path))?;
+        attributes.add_file(path.parent().unwrap_or(Path::new("")), &content);
+    }
+    Ok(attributes)
+}
+
+/// Walks a directory tree and builds its language breakdown.
+///
+/// Overrides from `.gitattributes` files in the tree (see [`GitAttributes`])
+/// are applied, then vendored files are left out of the per-language totals.
+/// Files that fail to classify are recorded in [`Breakdown::errors`] rather
+/// than aborting the scan. If [`ScanOptions::cache_dir`] is set, unchanged
+/// files are served from the cache, which is updated afterwards.
+///
+/// If `root` is a zip or tar archive, its entries are classified by their
+/// paths inside the archive without extracting it (see
+/// [`archive::analyze_archive`]). Archives are never cached.
+///
+/// # Examples
+///
+/// ```no_run
+/// use linguist::repository::{ScanOptions, analyze};
+///
+/// let breakdown = analyze(".", &ScanOptions::default())?;
+/// for (language, stats) in breakdown.sorted_languages() {
+///     println!("{language}: {} bytes", stats.bytes);
+/// }
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn analyze<P: AsRef<Path>>(root: P, options: &ScanOptions) -> Result<Breakdown> {
+    let root = root.as_ref();
+    if is_archive_file(root) {
+        return archive::analyze_archive(root, options);
+    }
+
+    let (mut paths, mut pruned) = if options.prune_vendored {
+        walk_pruned(root, options)?
+    } else {
+        (walk(root)?, Vec::new())
+    };
+    let mut attributes = load_gitattributes(root, &paths, &options.overrides)?;
+
+    // Files below a pruned directory could be marked as not vendored
+    if !pruned.is_empty() && attributes.unvendors_any() {
+        paths = walk(root)?;
+        pruned.clear();
+        attributes = load_gitattributes(root, &paths, &options.overrides)?;
+    }
+    paths.retain(|path| options.filter.matches(path));
+
+    let Some(ref cache_dir) = options.cache_dir else {
+        let results = parallel_map(&paths, options.jobs, |path| {
+            scan_file(root, path, options, &attributes)
+        });
+        let mut breakdown = collect_breakdown(&paths, results);
+        breakdown.pruned = pruned;
+        return Ok(breakdown);
+    };
+
+    // Only files that changed since the cache was written are scanned again.
+    // The metadata is captured before scanning, so a file modified mid-scan
+    // is picked up again next time rather than cached with a stale result
+    //
+    let mut cache = Cache::load(cache_dir, root, options, &attributes);
+    let results = parallel_map(&paths, options.jobs, |path| {
+        let metadata = fs::metadata(root.join(path))?;
+        let entry = match cache.get(path, &metadata) {
+            Some(entry) => entry,
+            None => scan_file(root, path, options, &attributes)?,
+        };
+        Ok((entry, metadata))
+    });
+
+    let mut entries = Vec::with_capacity(results.len());
+    for result in results {
+        entries.push(result.map(|(entry, metadata): (FileEntry, Metadata)| {
+            cache.insert(&entry, &metadata);
+            entry
+        }));
+    }
+    cache.save()?;
+
+    let mut breakdown = collect_breakdown(&paths, entries);
+    breakdown.pruned = pruned;
+    Ok(breakdown)
+}
+
+/// Classifies a file in a walked tree, applies the `.gitattributes` overrides
+/// and then counts its lines and finds embedded languages if requested
+fn scan_file(
+    root: &Path,
+    relative: &Path,
+    options: &ScanOptions,
+    attributes: &GitAttributes,
+) -> Result<FileEntry> {
+    let mut entry = classify_file(root, relative, options)?;
+    attributes.apply(&mut entry);
+
+    if options.count_lines
+        && let Some(language) = entry.language
+    {
+        let file = File::open(root.join(relative))?;
+        entry.lines = Some(count_lines(BufReader::new(file), language)?);
+    }
+
+    if options.embedded_languages
+        && let Some(language) = entry.language
+        && may_embed(language)
+    {
+        entry.embedded = find_embedded(language, File::open(root.join(relative))?)?;
+    }
+
+    Ok(entry)
+}
+
+/// Builds a breakdown from per-path results, recording failures as errors
+pub(crate) fn collect_breakdown(paths: &[PathBuf], results: Vec<Result<FileEntry>>) -> Breakdown {
+    let mut breakdown = Breakdown::default();
+    for (path, result) in paths.iter().zip(results) {
+        match result {
+            Ok(entry) => breakdown.add(entry),
+            Err(e) => breakdown.errors.push((path.clone(), e)),
+        }
+    }
+    breakdown
+}
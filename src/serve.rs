@@ -0,0 +1,206 @@
+//! Local HTTP server exposing detection as a JSON API.
+//!
+//! Endpoints:
+//!
+//! - `POST /detect` with `{"filename": "...", "content": "..."}` returns the
+//!   same report as `linguist --format json FILE`
+//! - `GET /languages/{name}` returns the definition of a language, looked up
+//!   by name or alias
+//! - `POST /breakdown` with a tar (optionally gzipped) body returns the same
+//!   report as `linguist stats --format json`; `?lines=true` and
+//!   `?embedded=true` match `--lines` and `--embedded`
+//!
+//! The `.linguist.toml` found from the current directory applies as it does
+//! on the command line. Request bodies are limited to 16 MiB. Errors are
+//! returned as `{"error": "..."}` with a 4xx status.
+
+use crate::cli::DetectionMethods;
+use crate::report::{self, BreakdownReport, ErrorReport, LanguageReport};
+use linguist::archive::analyze_tar;
+use linguist::repository::ScanOptions;
+use linguist::{LinguistError, find_language};
+use serde::Deserialize;
+use std::io::{self, Read, Write};
+use std::thread;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Largest request body accepted, by `/detect` and `/breakdown` alike
+const MAX_BODY: u64 = 16 * 1024 * 1024;
+
+/// Body of a `/detect` request
+#[derive(Deserialize)]
+struct DetectRequest {
+    filename: String,
+    #[serde(default)]
+    content: String,
+}
+
+/// A failed request, answered with a status code and an error report
+struct HttpError {
+    status: u16,
+    message: String,
+}
+
+impl HttpError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        HttpError {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// Serves requests on `bind` until the process is stopped, handling up to
+/// `jobs` requests at a time.
+///
+/// `/detect` uses `methods`, and the read limits and overrides of `options`,
+/// which `/breakdown` uses in full.
+///
+/// The bound address is printed once the server is listening, which tells
+/// callers the port when binding to port 0.
+pub fn run(
+    bind: &str,
+    jobs: usize,
+    methods: DetectionMethods,
+    options: &ScanOptions,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let server = Server::http(bind)?;
+    println!("Listening on http://{}", server.server_addr());
+    io::stdout().flush()?;
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.max(1) {
+            scope.spawn(|| {
+                loop {
+                    match server.recv() {
+                        Ok(request) => handle(request, methods, options),
+                        Err(e) => {
+                            eprintln!("Error accepting request: {e}");
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+fn handle(mut request: Request, methods: DetectionMethods, options: &ScanOptions) {
+    let (status, body) = match route(&mut request, methods, options) {
+        Ok(body) => (200, body),
+        Err(e) => (e.status, report::to_json(&ErrorReport { error: e.message })),
+    };
+
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("Content-Type header is valid");
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(content_type);
+    if let Err(e) = request.respond(response) {
+        eprintln!("Error sending response: {e}");
+    }
+}
+
+fn route(
+    request: &mut Request,
+    methods: DetectionMethods,
+    options: &ScanOptions,
+) -> Result<String, HttpError> {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let method = request.method().clone();
+
+    match (method, path) {
+        (Method::Post, "/detect") => detect(request, methods, options),
+        (Method::Post, "/breakdown") => breakdown(request, query, options),
+        (Method::Get, _) if path.starts_with("/languages/") => {
+            language(&path["/languages/".len()..])
+        }
+        (_, "/detect" | "/breakdown") => Err(HttpError::new(405, "Use POST")),
+        (_, _) if path.starts_with("/languages/") => Err(HttpError::new(405, "Use GET")),
+        _ => Err(HttpError::new(404, format!("No such endpoint: {path}"))),
+    }
+}
+
+fn detect(
+    request: &mut Request,
+    methods: DetectionMethods,
+    options: &ScanOptions,
+) -> Result<String, HttpError> {
+    let body = read_body(request)?;
+    let detect: DetectRequest = serde_json::from_slice(&body)
+        .map_err(|e| HttpError::new(400, format!("Invalid request: {e}")))?;
+    let mut report = report::detect_file(
+        &detect.filename,
+        methods,
+        options.read_limit,
+        &options.heuristic_limits,
+        || Ok(detect.content.as_bytes()),
+    );
+    report.apply_overrides(&options.overrides, methods);
+    Ok(report::to_json(&report))
+}
+
+fn breakdown(
+    request: &mut Request,
+    query: &str,
+    options: &ScanOptions,
+) -> Result<String, HttpError> {
+    let flag = |name: &str| {
+        query
+            .split('&')
+            .any(|pair| pair == name || pair == format!("{name}=true"))
+    };
+    let options = ScanOptions {
+        count_lines: flag("lines"),
+        embedded_languages: flag("embedded"),
+        ..options.clone()
+    };
+
+    let body = read_body(request)?;
+    let breakdown = analyze_tar(&body[..], &options).map_err(|e| match e {
+        LinguistError::InvalidArchive(_) => HttpError::new(400, e.to_string()),
+        e => HttpError::new(500, e.to_string()),
+    })?;
+    Ok(report::to_json(&BreakdownReport::new(
+        &breakdown,
+        options.count_lines,
+    )))
+}
+
+/// Reads a request body of at most [`MAX_BODY`] bytes
+fn read_body(request: &mut Request) -> Result<Vec<u8>, HttpError> {
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| HttpError::new(400, format!("Failed to read request: {e}")))?;
+    if body.len() as u64 > MAX_BODY {
+        return Err(HttpError::new(413, "Request body too large"));
+    }
+    Ok(body)
+}
+
+fn language(name: &str) -> Result<String, HttpError> {
+    let name = percent_decode(name).ok_or_else(|| HttpError::new(400, "Invalid language name"))?;
+    let language = find_language(&name)
+        .ok_or_else(|| HttpError::new(404, format!("Unknown language: {name}")))?;
+    Ok(report::to_json(&LanguageReport::from(language)))
+}
+
+/// Decodes `%XX` escapes in a path segment, e.g. `C%2B%2B` or `Common%20Lisp`
+fn percent_decode(segment: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut input = segment.bytes();
+    while let Some(byte) = input.next() {
+        if byte == b'%' {
+            let hex = [input.next()?, input.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
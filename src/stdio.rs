@@ -0,0 +1,161 @@
+//! Long-running detection over stdin and stdout.
+//!
+//! Each line of stdin is a JSON request such as
+//! `{"id": 1, "path": "src/foo.h", "content": "..."}`, where `content` is
+//! optional and read from `path` on disk when missing. Each request is
+//! answered with one line on stdout, `{"id": 1, "result": {...}}` holding the
+//! same report as `linguist --format json FILE`, or `{"id": 1, "error": "..."}`.
+//! Like that report, it applies the `.linguist.toml` found from the current
+//! directory.
+//!
+//! Requests are processed concurrently, so responses can arrive out of order
+//! and should be matched to requests by `id`. The process exits once stdin is
+//! closed and every request has been answered, or with an error once stdout
+//! can't be written to, e.g. because the reader went away.
+
+use crate::cli::DetectionMethods;
+use crate::report::{self, FileReport};
+use linguist::HeuristicLimits;
+use linguist::gitattributes::GitAttributes;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+use std::sync::{Mutex, mpsc};
+use std::thread;
+
+/// A detection request
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    path: String,
+    content: Option<String>,
+}
+
+/// The answer to a request, carrying its id back
+#[derive(Serialize)]
+struct Response {
+    id: Value,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<FileReport>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Answers requests from stdin on `jobs` worker threads until stdin closes,
+/// applying `overrides` to every result.
+///
+/// Fails with the first error writing a response, after which no more
+/// requests are read.
+pub fn run(
+    methods: DetectionMethods,
+    read_limit: usize,
+    limits: HeuristicLimits,
+    overrides: &GitAttributes,
+    jobs: usize,
+) -> io::Result<()> {
+    // Bounded, so that stdin isn't read much faster than requests are answered
+    let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(jobs.max(1));
+    let receiver = Mutex::new(Some(receiver));
+    let write_error: Mutex<Option<io::Error>> = Mutex::new(None);
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.max(1) {
+            scope.spawn(|| {
+                loop {
+                    // Hold the lock only while waiting for the next line
+                    let line = match receiver.lock().expect("receiver lock poisoned").as_ref() {
+                        Some(receiver) => match receiver.recv() {
+                            Ok(line) => line,
+                            Err(_) => break,
+                        },
+                        None => break,
+                    };
+                    let response = answer(&line, methods, read_limit, &limits, overrides);
+
+                    // Responses are written whole, one per line, so they never interleave
+                    let json =
+                        serde_json::to_string(&response).expect("Responses always serialize");
+                    let mut stdout = io::stdout().lock();
+                    if let Err(e) = writeln!(stdout, "{json}").and_then(|_| stdout.flush()) {
+                        // Dropping the receiver makes the reader's next send
+                        // fail, rather than block once the channel is full
+                        write_error
+                            .lock()
+                            .expect("error lock poisoned")
+                            .get_or_insert(e);
+                        receiver.lock().expect("receiver lock poisoned").take();
+                        break;
+                    }
+                }
+            });
+        }
+
+        // Lines are passed on as bytes, so that one that isn't UTF-8 is answered
+        // with an error like any other invalid request
+        let mut stdin = io::stdin().lock();
+        let mut line = Vec::new();
+        while stdin.read_until(b'\n', &mut line)? > 0 {
+            if line.trim_ascii().is_empty() {
+                line.clear();
+                continue;
+            }
+            if sender.send(std::mem::take(&mut line)).is_err() {
+                break;
+            }
+        }
+
+        // Closing the channel lets the workers finish once it's drained
+        drop(sender);
+        Ok::<_, io::Error>(())
+    })?;
+
+    match write_error.into_inner().expect("error lock poisoned") {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+fn answer(
+    line: &[u8],
+    methods: DetectionMethods,
+    read_limit: usize,
+    limits: &HeuristicLimits,
+    overrides: &GitAttributes,
+) -> Response {
+    let request: Request = match serde_json::from_slice(line) {
+        Ok(request) => request,
+        Err(e) => {
+            // Recover the id if the line is JSON at all, so the error can be matched
+            let id = serde_json::from_slice::<Value>(line)
+                .ok()
+                .and_then(|value| value.get("id").cloned())
+                .unwrap_or(Value::Null);
+            return Response {
+                id,
+                result: None,
+                error: Some(format!("Invalid request: {e}")),
+            };
+        }
+    };
+
+    let mut result = match request.content {
+        Some(ref content) => {
+            report::detect_file(&request.path, methods, read_limit, limits, || {
+                Ok(content.as_bytes())
+            })
+        }
+        None => report::detect_file(&request.path, methods, read_limit, limits, || {
+            Ok(File::open(&request.path)?)
+        }),
+    };
+    result.apply_overrides(overrides, methods);
+    Response {
+        id: request.id,
+        result: Some(result),
+        error: None,
+    }
+}
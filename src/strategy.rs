@@ -0,0 +1,416 @@
+//! The detection pipeline, as a list of strategies run in turn.
+//!
+//! Each [`Strategy`] is given the path of a file, its content if it has been
+//! read, and the candidate languages found so far, and narrows them down:
+//!
+//! - A non-empty result replaces the candidates, an empty one leaves them as
+//!   they are.
+//! - Once a single candidate remains, the later strategies don't run.
+//! - The content is only read, up to a limit, when the first strategy that
+//!   needs it is reached. Earlier strategies are given `None`.
+//! - A Jupyter notebook left at the end is given its kernel language as
+//!   [`DetectedLanguage::embedded`].
+//!
+//! [`Pipeline::default`] runs the built-in [`Filename`], [`Extension`],
+//! [`Shebang`] and [`Heuristics`] strategies, in that order, like
+//! [`detect_reader`](crate::detect_reader). Custom strategies can be inserted
+//! anywhere among them:
+//!
+//! ```
+//! use linguist::strategy::{Pipeline, Strategy};
+//! use linguist::{DetectedLanguage, Result, find_language};
+//! use std::path::Path;
+//!
+//! /// RAML documents are YAML files starting with a `#%RAML` header
+//! struct Raml;
+//!
+//! impl Strategy for Raml {
+//!     fn name(&self) -> &str {
+//!         "raml"
+//!     }
+//!
+//!     fn needs_content(&self) -> bool {
+//!         true
+//!     }
+//!
+//!     fn call(
+//!         &self,
+//!         path: &Path,
+//!         content: Option<&str>,
+//!         _candidates: &[DetectedLanguage],
+//!     ) -> Result<Vec<DetectedLanguage>> {
+//!         let is_yaml = path.extension().is_some_and(|ext| ext == "yml" || ext == "yaml");
+//!         let is_raml = content.is_some_and(|content| content.starts_with("#%RAML"));
+//!         if is_yaml && is_raml {
+//!             Ok(find_language("RAML").into_iter().collect())
+//!         } else {
+//!             Ok(Vec::new())
+//!         }
+//!     }
+//! }
+//!
+//! // Before the extension decides that the file is YAML
+//! let mut pipeline = Pipeline::default();
+//! let index = pipeline.position("extension").unwrap();
+//! pipeline.strategies.insert(index, Box::new(Raml));
+//!
+//! let langs = pipeline.detect("api.yml", "#%RAML 1.0\ntitle: API\n".as_bytes(), 1024)?;
+//! assert_eq!(langs[0].name, "RAML");
+//! # Ok::<(), linguist::LinguistError>(())
+//! ```
+
+use crate::notebook::{NOTEBOOK_LANGUAGE, inspect_notebook};
+use crate::{
+    DetectedLanguage, HeuristicLimits, HeuristicOutcome, LinguistError, Result,
+    detect_language_by_extension, detect_language_by_filename, detect_language_by_shebang,
+    disambiguate_with_limits, utils,
+};
+use std::fmt;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+/// One step of the detection pipeline.
+///
+/// Strategies are shared by the threads of a repository scan, so they must be
+/// `Send` and `Sync`.
+pub trait Strategy: Send + Sync {
+    /// Name identifying the strategy in a pipeline, e.g. `"heuristics"`
+    fn name(&self) -> &str;
+
+    /// Whether the strategy looks at the content of files. The content is
+    /// read before the first such strategy runs.
+    fn needs_content(&self) -> bool {
+        false
+    }
+
+    /// Narrows down the candidate languages of a file.
+    ///
+    /// `content` is the prefix of the file read by the pipeline, or `None` if
+    /// no strategy so far has needed it. `candidates` is empty if no strategy
+    /// so far has found any.
+    ///
+    /// Returns the narrowed candidates, or an empty vector to leave them as
+    /// they are.
+    fn call(
+        &self,
+        path: &Path,
+        content: Option<&str>,
+        candidates: &[DetectedLanguage],
+    ) -> Result<Vec<DetectedLanguage>>;
+
+    /// Same as [`Strategy::call`], also returning warnings about the file, such
+    /// as heuristic rules given up on. The default has none.
+    fn call_with_warnings(
+        &self,
+        path: &Path,
+        content: Option<&str>,
+        candidates: &[DetectedLanguage],
+    ) -> Result<HeuristicOutcome> {
+        Ok(HeuristicOutcome {
+            languages: self.call(path, content, candidates)?,
+            warnings: Vec::new(),
+        })
+    }
+}
+
+/// A shared strategy, e.g. one also used by other pipelines
+impl<S: Strategy + ?Sized> Strategy for Arc<S> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn needs_content(&self) -> bool {
+        (**self).needs_content()
+    }
+
+    fn call(
+        &self,
+        path: &Path,
+        content: Option<&str>,
+        candidates: &[DetectedLanguage],
+    ) -> Result<Vec<DetectedLanguage>> {
+        (**self).call(path, content, candidates)
+    }
+
+    fn call_with_warnings(
+        &self,
+        path: &Path,
+        content: Option<&str>,
+        candidates: &[DetectedLanguage],
+    ) -> Result<HeuristicOutcome> {
+        (**self).call_with_warnings(path, content, candidates)
+    }
+}
+
+/// Strategies run in turn to detect the language of a file.
+pub struct Pipeline {
+    /// The strategies, in the order they run
+    pub strategies: Vec<Box<dyn Strategy>>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Pipeline::with_heuristic_limits(HeuristicLimits::default())
+    }
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("strategies", &self.names())
+            .finish()
+    }
+}
+
+impl Pipeline {
+    /// The built-in strategies, running heuristics under `limits`.
+    pub fn with_heuristic_limits(limits: HeuristicLimits) -> Self {
+        Pipeline {
+            strategies: vec![
+                Box::new(Filename),
+                Box::new(Extension),
+                Box::new(Shebang),
+                Box::new(Heuristics { limits }),
+            ],
+        }
+    }
+
+    /// The index of the first strategy with the given name.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.strategies
+            .iter()
+            .position(|strategy| strategy.name() == name)
+    }
+
+    /// The names of the strategies, in order.
+    pub fn names(&self) -> Vec<&str> {
+        self.strategies
+            .iter()
+            .map(|strategy| strategy.name())
+            .collect()
+    }
+
+    /// Detects the language(s) of a file, reading at most `limit` bytes of its
+    /// content from `reader` if a strategy needs it.
+    ///
+    /// Returns the candidates left after the last strategy to run, which are
+    /// empty if no strategy found any. If a Jupyter notebook is left, the rest
+    /// of `reader` is parsed, keeping only the size of each cell, for the
+    /// kernel language in its metadata, which sets
+    /// [`DetectedLanguage::embedded`].
+    ///
+    /// Warnings, such as heuristic rules given up on, are dropped; use
+    /// [`Pipeline::detect_with_warnings`] to see them.
+    pub fn detect<P: AsRef<Path>, R: Read>(
+        &self,
+        filepath: P,
+        reader: R,
+        limit: usize,
+    ) -> Result<Vec<DetectedLanguage>> {
+        Ok(self
+            .detect_with_warnings(filepath, reader, limit)?
+            .languages)
+    }
+
+    /// Same as [`Pipeline::detect`], also returning the warnings of the
+    /// strategies that ran.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use linguist::HeuristicLimits;
+    /// use linguist::strategy::Pipeline;
+    ///
+    /// // Too little backtracking for the Smarty rule, so .tpl stays ambiguous
+    /// let limits = HeuristicLimits { backtrack_limit: 1, ..HeuristicLimits::default() };
+    /// let pipeline = Pipeline::with_heuristic_limits(limits);
+    /// let outcome = pipeline.detect_with_warnings("page.tpl", "{$title}\n".as_bytes(), 1024)?;
+    /// assert!(outcome.languages.len() > 1);
+    /// assert!(!outcome.warnings.is_empty());
+    /// # Ok::<(), linguist::LinguistError>(())
+    /// ```
+    pub fn detect_with_warnings<P: AsRef<Path>, R: Read>(
+        &self,
+        filepath: P,
+        mut reader: R,
+        limit: usize,
+    ) -> Result<HeuristicOutcome> {
+        let (mut outcome, content) = self.run(filepath.as_ref(), &mut reader, limit)?;
+
+        if let [notebook] = &mut outcome.languages[..]
+            && notebook.name == NOTEBOOK_LANGUAGE
+        {
+            let prefix = Cursor::new(content.unwrap_or_default());
+            notebook.embedded = match inspect_notebook(prefix.chain(reader)) {
+                Ok(info) => info.language.map(|lang| lang.name),
+                Err(LinguistError::InvalidNotebook(_)) => None,
+                Err(e) => return Err(e),
+            };
+        }
+        Ok(outcome)
+    }
+
+    /// Same as [`Pipeline::detect`], but never reads past `limit`, leaving
+    /// notebooks to scans that look into them when asked to.
+    pub(crate) fn classify<R: Read>(
+        &self,
+        path: &Path,
+        mut reader: R,
+        limit: usize,
+    ) -> Result<Vec<DetectedLanguage>> {
+        Ok(self.run(path, &mut reader, limit)?.0.languages)
+    }
+
+    /// Runs the strategies, returning the candidates left with the warnings
+    /// on the way, and the content if it was read
+    fn run<R: Read>(
+        &self,
+        path: &Path,
+        reader: &mut R,
+        limit: usize,
+    ) -> Result<(HeuristicOutcome, Option<String>)> {
+        let mut content: Option<String> = None;
+        let mut outcome = HeuristicOutcome::default();
+
+        for strategy in &self.strategies {
+            if outcome.languages.len() == 1 {
+                break;
+            }
+            if strategy.needs_content() && content.is_none() {
+                content = Some(utils::read_prefix(reader.by_ref(), limit)?);
+            }
+
+            let narrowed =
+                strategy.call_with_warnings(path, content.as_deref(), &outcome.languages)?;
+            outcome.warnings.extend(narrowed.warnings);
+            if !narrowed.languages.is_empty() {
+                outcome.languages = narrowed.languages;
+            }
+        }
+        Ok((outcome, content))
+    }
+}
+
+/// Detects languages by exact filename, e.g. `Makefile`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Filename;
+
+impl Strategy for Filename {
+    fn name(&self) -> &str {
+        "filename"
+    }
+
+    fn call(
+        &self,
+        path: &Path,
+        _content: Option<&str>,
+        candidates: &[DetectedLanguage],
+    ) -> Result<Vec<DetectedLanguage>> {
+        let mut languages = detect_language_by_filename(path)?;
+        if !candidates.is_empty() {
+            languages.retain(|lang| candidates.iter().any(|c| c.name == lang.name));
+        }
+        Ok(languages)
+    }
+}
+
+/// Detects languages by file extension, unless an earlier strategy found
+/// candidates.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Extension;
+
+impl Strategy for Extension {
+    fn name(&self) -> &str {
+        "extension"
+    }
+
+    fn call(
+        &self,
+        path: &Path,
+        _content: Option<&str>,
+        candidates: &[DetectedLanguage],
+    ) -> Result<Vec<DetectedLanguage>> {
+        // Exact filenames are the more specific signal
+        if !candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+        detect_language_by_extension(path)
+    }
+}
+
+/// Detects languages by the interpreter of a `#!` line, for files no earlier
+/// strategy found candidates for.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Shebang;
+
+impl Strategy for Shebang {
+    fn name(&self) -> &str {
+        "shebang"
+    }
+
+    fn needs_content(&self) -> bool {
+        true
+    }
+
+    fn call(
+        &self,
+        _path: &Path,
+        content: Option<&str>,
+        candidates: &[DetectedLanguage],
+    ) -> Result<Vec<DetectedLanguage>> {
+        match content {
+            Some(content) if candidates.is_empty() => detect_language_by_shebang(content),
+            _ => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Picks among the candidates with the content heuristics of heuristics.yml.
+///
+/// Rules exceeding the limits are taken as not matching, and reported as
+/// warnings by [`Strategy::call_with_warnings`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Heuristics {
+    /// Limits on the work heuristics do on a file
+    pub limits: HeuristicLimits,
+}
+
+impl Strategy for Heuristics {
+    fn name(&self) -> &str {
+        "heuristics"
+    }
+
+    fn needs_content(&self) -> bool {
+        true
+    }
+
+    fn call(
+        &self,
+        path: &Path,
+        content: Option<&str>,
+        candidates: &[DetectedLanguage],
+    ) -> Result<Vec<DetectedLanguage>> {
+        Ok(self
+            .call_with_warnings(path, content, candidates)?
+            .languages)
+    }
+
+    fn call_with_warnings(
+        &self,
+        path: &Path,
+        content: Option<&str>,
+        candidates: &[DetectedLanguage],
+    ) -> Result<HeuristicOutcome> {
+        let Some(content) = content else {
+            return Ok(HeuristicOutcome::default());
+        };
+        let mut outcome = disambiguate_with_limits(path, content, &self.limits)?;
+        if !candidates.is_empty() {
+            outcome
+                .languages
+                .retain(|lang| candidates.iter().any(|c| c.name == lang.name));
+        }
+        Ok(outcome)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::error::LinguistError;
-use std::path::Path;
+use std::io::Read;
+use std::path::{Component, Path};
 
 /// Type alias for Results in this crate
 pub type Result<T> = std::result::Result<T, LinguistError>;
@@ -39,6 +40,59 @@ pub fn get_filename_from_path(path: &Path) -> Result<&str> {
     Ok(filename_str)
 }
 
+/// Makes a path relative to a repository root, with forward slashes.
+///
+/// Paths starting with `root` (lexically, or once both are resolved for
+/// absolute paths) have it stripped, while other relative paths are taken to
+/// be relative to the root already. `.` components are dropped.
+///
+/// # Errors
+///
+/// Returns `LinguistError::InvalidPath` for paths outside the root, that go
+/// up with `..`, or that aren't valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::utils::root_relative_path;
+///
+/// assert_eq!(root_relative_path("/home/ci/repo", "/home/ci/repo/deps/x.c")?, "deps/x.c");
+/// assert_eq!(root_relative_path("/home/ci/repo", "./deps/x.c")?, "deps/x.c");
+/// assert!(root_relative_path("/home/ci/repo", "/etc/passwd").is_err());
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn root_relative_path<R: AsRef<Path>, P: AsRef<Path>>(root: R, path: P) -> Result<String> {
+    let (root, path) = (root.as_ref(), path.as_ref());
+    let outside =
+        || LinguistError::InvalidPath(format!("{} is outside {}", path.display(), root.display()));
+
+    let relative = match path.strip_prefix(root) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) if path.is_absolute() => {
+            // The root may be relative, or either may go through a symlink
+            let root = root.canonicalize().map_err(|_| outside())?;
+            let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            path.strip_prefix(&root)
+                .map_err(|_| outside())?
+                .to_path_buf()
+        }
+        Err(_) => path.to_path_buf(),
+    };
+
+    let mut parts = Vec::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => parts.push(
+                part.to_str()
+                    .ok_or_else(|| LinguistError::InvalidPath(format!("{path:?}")))?,
+            ),
+            Component::CurDir => {}
+            _ => return Err(outside()),
+        }
+    }
+    Ok(parts.join("/"))
+}
+
 /// Extract all possible extensions from a filename.
 ///
 /// Returns extensions from most specific to least specific.
@@ -70,10 +124,49 @@ pub fn extract_extensions(filename: &str) -> Vec<String> {
         .collect()
 }
 
+/// Extract the interpreter named by the `#!` line at the start of some content.
+///
+/// The path of the interpreter is dropped, `env` is looked through (skipping
+/// its options and variable assignments), and so is a trailing minor version,
+/// so `#!/usr/bin/env python3.11` names `python3`, as upstream.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::utils::shebang_interpreter;
+///
+/// assert_eq!(shebang_interpreter("#!/bin/sh\necho hi"), Some("sh".to_string()));
+/// assert_eq!(shebang_interpreter("#!/usr/bin/env -S ruby -w\n"), Some("ruby".to_string()));
+/// assert_eq!(shebang_interpreter("#! /usr/bin/python2.7"), Some("python2".to_string()));
+/// assert_eq!(shebang_interpreter("echo hi"), None);
+/// ```
+pub fn shebang_interpreter(content: &str) -> Option<String> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let line = content.strip_prefix("#!")?.lines().next()?;
+    let mut words = line.split_whitespace();
+
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+
+    // Drop a minor version, e.g. python2.7
+    //
+    if let Some((name, version)) = program.rsplit_once('.')
+        && !version.is_empty()
+        && version.bytes().all(|b| b.is_ascii_digit())
+    {
+        program = name;
+    }
+
+    (!program.is_empty()).then(|| program.to_string())
+}
+
 /// Helper function to check if any pattern in a list matches the content.
 ///
 /// Returns true if any regex pattern in the list matches the content,
-/// false if none match, or an error if any regex pattern is malformed.
+/// false if none match, or an error if any regex pattern is malformed or gives
+/// up within [`DEFAULT_BACKTRACK_LIMIT`](crate::DEFAULT_BACKTRACK_LIMIT).
 ///
 /// # Examples
 ///
@@ -86,19 +179,88 @@ pub fn extract_extensions(filename: &str) -> Vec<String> {
 /// assert!(!matches_pattern(&patterns, "goodbye").unwrap());
 /// ```
 pub fn matches_pattern(patterns: &[String], content: &str) -> Result<bool> {
+    matches_pattern_with_limit(patterns, content, crate::DEFAULT_BACKTRACK_LIMIT)
+}
+
+/// Same as [`matches_pattern`], but lets each pattern backtrack at most
+/// `backtrack_limit` times on the content.
+///
+/// Patterns using backreferences or look-around are run by a backtracking
+/// engine, which can take exponential time on hostile content. One that gives
+/// up fails with `LinguistError::RegexLimitExceeded`, while malformed
+/// patterns fail with `LinguistError::InvalidRegex`.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::LinguistError;
+/// use linguist::utils::matches_pattern_with_limit;
+///
+/// let patterns = vec![r"^(\w+)\s+\1$".to_string()];
+/// assert!(matches_pattern_with_limit(&patterns, "hello hello", 1_000).unwrap());
+/// assert!(matches!(
+///     matches_pattern_with_limit(&patterns, "hello hello", 1),
+///     Err(LinguistError::RegexLimitExceeded { .. })
+/// ));
+/// ```
+pub fn matches_pattern_with_limit(
+    patterns: &[String],
+    content: &str,
+    backtrack_limit: usize,
+) -> Result<bool> {
     for pattern in patterns {
-        let regex = fancy_regex::Regex::new(&format!("(?m){pattern}"))
+        let regex = fancy_regex::RegexBuilder::new(&format!("(?m){pattern}"))
+            .backtrack_limit(backtrack_limit)
+            .build()
             .map_err(|e| LinguistError::InvalidRegex {
                 pattern: pattern.clone(),
                 error: e.to_string(),
             })?;
 
-        if regex.is_match(content).map_err(|e| LinguistError::InvalidRegex {
-            pattern: pattern.clone(),
-            error: e.to_string(),
-        })? {
+        let matched = regex.is_match(content).map_err(|e| match e {
+            fancy_regex::Error::RuntimeError(_) => LinguistError::RegexLimitExceeded {
+                pattern: pattern.clone(),
+                error: e.to_string(),
+            },
+            _ => LinguistError::InvalidRegex {
+                pattern: pattern.clone(),
+                error: e.to_string(),
+            },
+        })?;
+        if matched {
             return Ok(true);
         }
     }
     Ok(false)
 }
+
+/// Reads at most `limit` bytes from a reader and decodes them as UTF-8.
+///
+/// Only the prefix is ever held in memory, so this is safe to call on
+/// arbitrarily large inputs. Invalid UTF-8 sequences (including a multi-byte
+/// character cut in half by the limit) are replaced rather than treated as an
+/// error, since heuristics only need an approximate view of the content.
+///
+/// # Examples
+///
+/// ```
+/// use linguist::utils::read_prefix;
+///
+/// let content = read_prefix("hello world".as_bytes(), 5)?;
+/// assert_eq!(content, "hello");
+/// # Ok::<(), linguist::LinguistError>(())
+/// ```
+pub fn read_prefix<R: Read>(reader: R, limit: usize) -> Result<String> {
+    let mut buffer = Vec::with_capacity(limit.min(64 * 1024));
+    reader.take(limit as u64).read_to_end(&mut buffer)?;
+
+    // Drop a trailing partial character left behind by the byte limit
+    //
+    if let Err(e) = std::str::from_utf8(&buffer)
+        && e.error_len().is_none()
+    {
+        buffer.truncate(e.valid_up_to());
+    }
+
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
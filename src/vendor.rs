@@ -0,0 +1,189 @@
+//! Matching paths against the vendor.yml patterns.
+//!
+//! Testing every pattern in turn is one of the main costs of walking a large
+//! tree, so the patterns are compiled into as few passes as possible:
+//!
+//! - Every pattern the `regex` crate supports goes into a [`RegexSet`], one
+//!   for the patterns every match of which contains one of a few literal
+//!   strings and one for the rest.
+//! - The literals are searched for with a single Aho-Corasick automaton, and
+//!   the first set is only run when one of them is found. Most paths contain
+//!   none and are rejected without running any regex.
+//! - The few patterns needing `fancy_regex` (look-around, backreferences) are
+//!   run one by one, and their errors (e.g. hitting the backtrack limit) are
+//!   returned rather than taken as a non-match.
+
+use crate::{LinguistError, Result};
+use aho_corasick::AhoCorasick;
+use regex::RegexSet;
+use regex_syntax::hir::literal::{ExtractKind, Extractor};
+
+/// Literals shorter than this reject too few paths to be worth searching for
+const MIN_LITERAL_LEN: usize = 2;
+
+/// Compiled vendor patterns, reporting the first match in their order.
+#[derive(Debug)]
+pub struct VendorMatcher {
+    /// Every pattern, in order
+    patterns: Vec<String>,
+
+    /// Patterns with required literals, only run when one of them is found
+    literals: Option<AhoCorasick>,
+    prefiltered: PatternSet,
+
+    /// Patterns without required literals, always run
+    unfiltered: PatternSet,
+
+    /// Patterns the `regex` crate doesn't support, in order
+    fancy: Vec<(usize, fancy_regex::Regex)>,
+}
+
+/// A `RegexSet` and the index in `VendorMatcher::patterns` of each of its patterns
+#[derive(Debug)]
+struct PatternSet {
+    set: RegexSet,
+    indices: Vec<usize>,
+}
+
+impl VendorMatcher {
+    /// Compiles patterns, returning the ones that aren't valid for either
+    /// regex engine alongside the matcher, which leaves them out.
+    pub fn new(patterns: &[String]) -> (Self, Vec<LinguistError>) {
+        let mut errors = Vec::new();
+        let mut kept = Vec::new();
+        let mut literals = Vec::new();
+        let mut prefiltered = Vec::new();
+        let mut unfiltered = Vec::new();
+        let mut fancy = Vec::new();
+
+        for pattern in patterns {
+            let index = kept.len();
+            if regex::Regex::new(pattern).is_ok() {
+                match required_literals(pattern) {
+                    Some(required) => {
+                        literals.extend(required);
+                        prefiltered.push((index, pattern));
+                    }
+                    None => unfiltered.push((index, pattern)),
+                }
+            } else {
+                match fancy_regex::Regex::new(pattern) {
+                    Ok(regex) => fancy.push((index, regex)),
+                    Err(e) => {
+                        errors.push(LinguistError::InvalidRegex {
+                            pattern: pattern.clone(),
+                            error: e.to_string(),
+                        });
+                        continue;
+                    }
+                }
+            }
+            kept.push(pattern.clone());
+        }
+
+        let literals = (!literals.is_empty()).then(|| {
+            AhoCorasick::new(&literals).expect("Literal strings always build an automaton")
+        });
+        let matcher = VendorMatcher {
+            patterns: kept,
+            literals,
+            prefiltered: PatternSet::new(prefiltered),
+            unfiltered: PatternSet::new(unfiltered),
+            fancy,
+        };
+        (matcher, errors)
+    }
+
+    /// The first pattern, in the order given, matching a path.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LinguistError::RegexLimitExceeded` if a `fancy_regex` pattern
+    /// gives up on the path, e.g. by exceeding its backtrack limit, like
+    /// heuristics do, and `LinguistError::InvalidRegex` if it fails otherwise.
+    pub fn first_match(&self, path: &str) -> Result<Option<&str>> {
+        let mut first: Option<usize> = None;
+
+        let prefilter_hit = self
+            .literals
+            .as_ref()
+            .is_some_and(|literals| literals.is_match(path));
+        if prefilter_hit {
+            first = self.prefiltered.first_match(path);
+        }
+        if let Some(index) = self.unfiltered.first_match(path) {
+            first = Some(first.map_or(index, |first| first.min(index)));
+        }
+
+        // Only patterns listed before the best match so far can change it
+        for (index, regex) in &self.fancy {
+            if first.is_some_and(|first| first < *index) {
+                break;
+            }
+            let matched = regex.is_match(path).map_err(|e| match e {
+                fancy_regex::Error::RuntimeError(_) => LinguistError::RegexLimitExceeded {
+                    pattern: regex.as_str().to_string(),
+                    error: e.to_string(),
+                },
+                _ => LinguistError::InvalidRegex {
+                    pattern: regex.as_str().to_string(),
+                    error: e.to_string(),
+                },
+            })?;
+            if matched {
+                first = Some(*index);
+                break;
+            }
+        }
+
+        Ok(first.map(|index| self.patterns[index].as_str()))
+    }
+}
+
+impl PatternSet {
+    fn new(patterns: Vec<(usize, &String)>) -> Self {
+        let (indices, patterns): (Vec<usize>, Vec<&String>) = patterns.into_iter().unzip();
+        PatternSet {
+            set: RegexSet::new(patterns).expect("Each pattern compiled on its own"),
+            indices,
+        }
+    }
+
+    /// The index of the first pattern of the set matching a path
+    fn first_match(&self, path: &str) -> Option<usize> {
+        self.set
+            .matches(path)
+            .iter()
+            .next()
+            .map(|i| self.indices[i])
+    }
+}
+
+/// Literals one of which every match of a pattern contains, if there are any
+/// worth searching for.
+///
+/// Every match starts with one of the pattern's prefix literals and ends with
+/// one of its suffix literals, so either set will do; the one whose shortest
+/// literal is longest rejects the most paths.
+fn required_literals(pattern: &str) -> Option<Vec<Vec<u8>>> {
+    let hir = regex_syntax::parse(pattern).ok()?;
+
+    let mut best: Option<Vec<Vec<u8>>> = None;
+    for kind in [ExtractKind::Prefix, ExtractKind::Suffix] {
+        let seq = Extractor::new().kind(kind).extract(&hir);
+        let Some(literals) = seq.literals() else {
+            continue; // Matches can start (or end) with anything
+        };
+        let shortest = literals.iter().map(|l| l.as_bytes().len()).min();
+        if shortest.is_none_or(|len| len < MIN_LITERAL_LEN) {
+            continue;
+        }
+        let best_shortest = best
+            .as_ref()
+            .and_then(|best| best.iter().map(Vec::len).min());
+        if best_shortest.is_none_or(|len| shortest > Some(len)) {
+            best = Some(literals.iter().map(|l| l.as_bytes().to_vec()).collect());
+        }
+    }
+    best
+}
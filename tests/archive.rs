@@ -0,0 +1,301 @@
+mod test_archive {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use linguist::LinguistError;
+    use linguist::archive::{analyze_tar, analyze_zip, is_archive};
+    use linguist::repository::{self, ScanOptions};
+    use std::io::{Cursor, Write};
+    use xz2::write::XzEncoder;
+    use zip::write::SimpleFileOptions;
+
+    /// Helper: Build an uncompressed tar archive from (path, content) pairs
+    fn tar(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    /// Helper: Gzip some bytes
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Helper: Compress some bytes with xz
+    fn xz(data: &[u8]) -> Vec<u8> {
+        let mut encoder = XzEncoder::new(Vec::new(), 6);
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Helper: Build a zip archive from (path, content) pairs
+    fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .add_directory("project/", SimpleFileOptions::default())
+            .unwrap();
+        for (path, content) in files {
+            writer
+                .start_file(path.trim_start_matches("./"), SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// Helper: A small project with a mix of languages
+    fn sample_files() -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("project/src/main.rs", "fn main() {}\n"),
+            ("./project/scripts/build.py", "print('hi')\n"),
+            (
+                "project/node_modules/left-pad/index.js",
+                "module.exports = 1;\n",
+            ),
+            ("project/.git/config", "[core]\n"),
+        ]
+    }
+
+    #[test]
+    fn tar_entries_are_classified_by_path() {
+        let breakdown = analyze_tar(tar(&sample_files()).as_slice(), &ScanOptions::default())
+            .expect("Should not error");
+
+        assert_eq!(breakdown.languages["Rust"].bytes, 13);
+        assert_eq!(breakdown.languages["Python"].bytes, 12);
+        assert!(!breakdown.languages.contains_key("JavaScript"));
+
+        let paths: Vec<_> = breakdown
+            .files
+            .iter()
+            .map(|f| f.path.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "project/node_modules/left-pad/index.js",
+                "project/scripts/build.py",
+                "project/src/main.rs",
+            ]
+        );
+        assert!(breakdown.files[0].vendored);
+    }
+
+    #[test]
+    fn gzipped_archives_are_detected() {
+        let plain = analyze_tar(tar(&sample_files()).as_slice(), &ScanOptions::default())
+            .expect("Should not error");
+        let gzipped = analyze_tar(
+            gzip(&tar(&sample_files())).as_slice(),
+            &ScanOptions::default(),
+        )
+        .expect("Should not error");
+        assert_eq!(plain.languages, gzipped.languages);
+    }
+
+    #[test]
+    fn gitattributes_apply_to_entries_before_them() {
+        let files = [
+            ("lib/util.inc", "<?php echo 1;\n"),
+            (".gitattributes", "*.inc linguist-language=PHP\n"),
+        ];
+        let breakdown =
+            analyze_tar(tar(&files).as_slice(), &ScanOptions::default()).expect("Should not error");
+        assert_eq!(breakdown.languages["PHP"].files, 1);
+    }
+
+    #[test]
+    fn binary_entries_are_left_out() {
+        let files = [
+            ("src/main.rs", "fn main() {}\n"),
+            ("build/tool.py", "\x7fELF\x02\x01\x01\0\0\0"),
+            ("assets/data.js", "\x01\x02\x03\x04var x;\x05\x06"),
+        ];
+        for breakdown in [
+            analyze_tar(tar(&files).as_slice(), &ScanOptions::default()),
+            analyze_zip(Cursor::new(zip(&files)), &ScanOptions::default()),
+        ] {
+            let breakdown = breakdown.expect("Should not error");
+            let paths: Vec<_> = breakdown.files.iter().map(|f| f.path.clone()).collect();
+            assert_eq!(paths, vec![std::path::PathBuf::from("src/main.rs")]);
+            assert!(!breakdown.languages.contains_key("Python"));
+            assert!(!breakdown.languages.contains_key("JavaScript"));
+        }
+    }
+
+    #[test]
+    fn generated_entries_are_left_out() {
+        let files = [
+            ("src/main.rs", "fn main() {}\n"),
+            ("src/schema.rs", "// @generated by diesel\nfn table() {}\n"),
+            ("api/user.pb.go", "package api\n"),
+            ("Cargo.lock", "version = 3\n"),
+        ];
+        let options = ScanOptions {
+            count_lines: true,
+            ..ScanOptions::default()
+        };
+        let breakdown = analyze_tar(tar(&files).as_slice(), &options).expect("Should not error");
+
+        let paths: Vec<_> = breakdown.files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(paths, vec![std::path::PathBuf::from("src/main.rs")]);
+        assert_eq!(breakdown.languages["Rust"].bytes, 13);
+        assert!(!breakdown.languages.contains_key("Go"));
+    }
+
+    #[test]
+    fn lines_are_counted_when_requested() {
+        let files = [("src/lib.rs", "// doc\n\nfn lib() {}\n")];
+        let options = ScanOptions {
+            count_lines: true,
+            ..ScanOptions::default()
+        };
+        let breakdown = analyze_tar(tar(&files).as_slice(), &options).expect("Should not error");
+        let lines = breakdown.languages["Rust"].lines;
+        assert_eq!((lines.code, lines.comment, lines.blank), (1, 1, 1));
+    }
+
+    #[test]
+    fn entries_are_counted_past_the_read_limit() {
+        let script = "let x = 1;\n".repeat(100);
+        let page = format!("<html>\n<script>\n{script}</script>\n</html>\n");
+        let notebook = r#"{"cells": [{"cell_type": "code", "source": "print(1)"}],
+            "metadata": {"kernelspec": {"language": "python"}}}"#;
+        let files = [
+            ("index.html", page.as_str()),
+            ("analysis.ipynb", notebook),
+            ("src/lib.rs", "// doc\n\nfn lib() {}\n"),
+        ];
+        let options = ScanOptions {
+            read_limit: 16,
+            count_lines: true,
+            embedded_languages: true,
+            ..ScanOptions::default()
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        let from_dir = repository::analyze(dir.path(), &options).expect("Should not error");
+        let from_tar = analyze_tar(tar(&files).as_slice(), &options).expect("Should not error");
+
+        assert_eq!(from_tar.languages, from_dir.languages);
+        // The script block starts with the newline after `<script>`
+        assert_eq!(
+            from_tar.languages["JavaScript"].bytes,
+            1 + script.len() as u64
+        );
+        assert_eq!(from_tar.languages["Python"].bytes, 8);
+        assert_eq!(from_tar.languages["HTML"].lines.total(), 104);
+    }
+
+    #[test]
+    fn corrupt_archives_are_errors() {
+        let mut data = tar(&sample_files());
+        data.truncate(700);
+        let result = analyze_tar(data.as_slice(), &ScanOptions::default());
+        assert!(matches!(result, Err(LinguistError::InvalidArchive(_))));
+    }
+
+    #[test]
+    fn xz_compressed_archives_are_detected() {
+        let plain = analyze_tar(tar(&sample_files()).as_slice(), &ScanOptions::default())
+            .expect("Should not error");
+        let compressed = analyze_tar(
+            xz(&tar(&sample_files())).as_slice(),
+            &ScanOptions::default(),
+        )
+        .expect("Should not error");
+        assert_eq!(plain.languages, compressed.languages);
+    }
+
+    #[test]
+    fn zip_entries_are_classified_like_tar_entries() {
+        let from_tar = analyze_tar(tar(&sample_files()).as_slice(), &ScanOptions::default())
+            .expect("Should not error");
+        let from_zip = analyze_zip(Cursor::new(zip(&sample_files())), &ScanOptions::default())
+            .expect("Should not error");
+
+        assert_eq!(from_tar.languages, from_zip.languages);
+        let paths: Vec<_> = from_zip.files.iter().map(|f| f.path.clone()).collect();
+        let expected: Vec<_> = from_tar.files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(paths, expected);
+        assert!(from_zip.files[0].vendored);
+    }
+
+    #[test]
+    fn zip_gitattributes_apply_to_every_entry() {
+        // Unlike a tar stream, the zip central directory is read up front
+        let files = [
+            ("lib/util.inc", "<?php echo 1;\n"),
+            (".gitattributes", "*.inc linguist-language=PHP\n"),
+        ];
+        let options = ScanOptions {
+            count_lines: true,
+            ..ScanOptions::default()
+        };
+        let breakdown = analyze_zip(Cursor::new(zip(&files)), &options).expect("Should not error");
+        assert_eq!(breakdown.languages["PHP"].files, 1);
+        assert_eq!(breakdown.languages["PHP"].lines.code, 1);
+    }
+
+    #[test]
+    fn corrupt_zip_archives_are_errors() {
+        let mut data = zip(&sample_files());
+        data.truncate(data.len() - 10);
+        let result = analyze_zip(Cursor::new(data), &ScanOptions::default());
+        assert!(matches!(result, Err(LinguistError::InvalidArchive(_))));
+    }
+
+    #[test]
+    fn archives_are_recognized_by_name() {
+        for name in [
+            "a.zip",
+            "a.tar",
+            "a.tar.gz",
+            "a.tgz",
+            "dir/a.tar.xz",
+            "A.TXZ",
+        ] {
+            assert!(is_archive(name), "{name}");
+        }
+        for name in ["a.gz", "a.xz", "zip", ".tar", "a.tar.bz2"] {
+            assert!(!is_archive(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn walk_and_analyze_accept_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let archives = [
+            ("drop.zip", zip(&sample_files())),
+            ("drop.tar.gz", gzip(&tar(&sample_files()))),
+            ("drop.tar.xz", xz(&tar(&sample_files()))),
+        ];
+        for (name, data) in archives {
+            let path = dir.path().join(name);
+            std::fs::write(&path, data).unwrap();
+
+            let paths = repository::walk(&path).expect("Should not error");
+            assert_eq!(paths.len(), 3, "{name}");
+            assert!(paths[0].ends_with("left-pad/index.js"), "{name}");
+
+            let breakdown =
+                repository::analyze(&path, &ScanOptions::default()).expect("Should not error");
+            assert_eq!(breakdown.languages["Rust"].bytes, 13, "{name}");
+            assert_eq!(breakdown.languages["Python"].bytes, 12, "{name}");
+        }
+    }
+}
@@ -82,9 +82,29 @@ mod test_detection_by_extension {
     }
 
     #[test]
-    fn case_sensitive() {
-        // Extensions are case-sensitive
-        assert_detects_none("file.RS");
+    fn case_insensitive_fallback() {
+        // Extensions not matching exactly are looked up ignoring case
+        assert_detects("file.RS", "Rust");
+        assert_detects_only("FOO.PY", "Python");
+        assert_detects_only("Main.JAVA", "Java");
+        assert_detects("SETUP.SH", "Shell");
+        assert_detects_none("file.XYZ123");
+    }
+
+    #[test]
+    fn mixed_case_extensions() {
+        // Extensions listed in mixed case match exactly and in any other case
+        assert_detects_only("Syntax.tmLanguage", "XML Property List");
+        assert_detects_only("SYNTAX.TMLANGUAGE", "XML Property List");
+        assert_detects_only("syntax.tmlanguage", "XML Property List");
+    }
+
+    #[test]
+    fn case_insensitive_compound_extensions() {
+        // The longest extension still wins when only its case differs
+        assert_detects_only("VIEW.BLADE.PHP", "Blade");
+        assert_detects_only("view.Blade.php", "Blade");
+        assert_detects("TYPES.D.TS", "TypeScript");
     }
 
     #[test]
@@ -0,0 +1,62 @@
+mod test_detection_by_shebang {
+    use linguist::detect_language_by_shebang;
+
+    /// Helper: Assert that the languages detected from some content include the expected language
+    fn assert_detects(content: &str, expected_language: &str) {
+        let langs = detect_language_by_shebang(content).expect("Should not error");
+        let detected_names: Vec<&str> = langs.iter().map(|lang| lang.name).collect();
+        assert!(
+            detected_names.contains(&expected_language),
+            "Expected '{}' to be detected for {:?}, but got: {:?}",
+            expected_language,
+            content,
+            detected_names
+        );
+    }
+
+    /// Helper: Assert that no language is detected
+    fn assert_detects_none(content: &str) {
+        let langs = detect_language_by_shebang(content).expect("Should not error");
+        assert!(
+            langs.is_empty(),
+            "Expected no language for {:?}, but got: {:?}",
+            content,
+            langs.iter().map(|lang| lang.name).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn detect_absolute_interpreter() {
+        assert_detects("#!/bin/bash\necho hi\n", "Shell");
+        assert_detects("#!/usr/bin/perl -w\n", "Perl");
+    }
+
+    #[test]
+    fn detect_through_env() {
+        assert_detects("#!/usr/bin/env python3\n", "Python");
+        assert_detects("#!/usr/bin/env -S node --harmony\n", "JavaScript");
+        assert_detects("#!/usr/bin/env LANG=C ruby\n", "Ruby");
+    }
+
+    #[test]
+    fn detect_versioned_interpreter() {
+        assert_detects("#!/usr/bin/python2.7\n", "Python");
+    }
+
+    #[test]
+    fn detect_after_bom() {
+        assert_detects("\u{feff}#!/bin/sh\n", "Shell");
+    }
+
+    #[test]
+    fn shebang_must_be_first_line() {
+        assert_detects_none("\n#!/bin/sh\n");
+        assert_detects_none("echo hi\n");
+        assert_detects_none("#!\n");
+    }
+
+    #[test]
+    fn unknown_interpreter() {
+        assert_detects_none("#!/usr/bin/not-an-interpreter\n");
+    }
+}
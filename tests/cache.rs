@@ -0,0 +1,101 @@
+mod test_cache {
+    use linguist::repository::{ScanOptions, analyze};
+    use std::fs::{self, File};
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    const CPP_HEADER: &str = "#include <iostream>\n";
+    const C_HEADER: &str = "#include <stdio.hh>\n";
+
+    /// Helper: Write a file and pin its modification time
+    fn write_with_mtime(path: &Path, content: &str, modified: SystemTime) {
+        fs::write(path, content).unwrap();
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    /// Helper: The language detected for `api.h` in the repository
+    fn header_language(root: &Path, cache_dir: &Path) -> Option<&'static str> {
+        let options = ScanOptions {
+            cache_dir: Some(cache_dir.to_path_buf()),
+            ..ScanOptions::default()
+        };
+        let breakdown = analyze(root, &options).expect("Should not error");
+        breakdown
+            .files
+            .iter()
+            .find(|f| f.path == Path::new("api.h"))
+            .and_then(|f| f.language)
+    }
+
+    #[test]
+    fn unchanged_files_are_served_from_cache() {
+        let repo = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        let header = repo.path().join("api.h");
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(CPP_HEADER.len(), C_HEADER.len());
+
+        write_with_mtime(&header, CPP_HEADER, modified);
+        assert_eq!(header_language(repo.path(), cache.path()), Some("C++"));
+        assert!(cache.path().join("results.json").exists());
+
+        // Same size and mtime: the stale cached result is (deliberately) reused
+        write_with_mtime(&header, C_HEADER, modified);
+        assert_eq!(header_language(repo.path(), cache.path()), Some("C++"));
+
+        // A new mtime invalidates the entry
+        write_with_mtime(&header, C_HEADER, modified + Duration::from_secs(1));
+        assert_eq!(header_language(repo.path(), cache.path()), Some("C"));
+    }
+
+    #[test]
+    fn changed_size_is_detected_again() {
+        let repo = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        let header = repo.path().join("api.h");
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        write_with_mtime(&header, CPP_HEADER, modified);
+        assert_eq!(header_language(repo.path(), cache.path()), Some("C++"));
+
+        write_with_mtime(&header, "#include <stdio.h>\nint x;\n", modified);
+        assert_eq!(header_language(repo.path(), cache.path()), Some("C"));
+    }
+
+    #[test]
+    fn concurrent_scans_share_a_cache() {
+        let repo = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        fs::write(repo.path().join("api.h"), CPP_HEADER).unwrap();
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    assert_eq!(header_language(repo.path(), cache.path()), Some("C++"));
+                });
+            }
+        });
+
+        // Only the cache itself is left behind
+        let names: Vec<_> = fs::read_dir(cache.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["results.json"]);
+    }
+
+    #[test]
+    fn corrupt_cache_is_ignored() {
+        let repo = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        fs::write(cache.path().join("results.json"), "not json").unwrap();
+        fs::write(repo.path().join("api.h"), CPP_HEADER).unwrap();
+
+        assert_eq!(header_language(repo.path(), cache.path()), Some("C++"));
+    }
+}
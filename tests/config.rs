@@ -0,0 +1,136 @@
+mod test_config {
+    use serde_json::Value;
+    use std::fs;
+    use std::path::Path;
+    use std::process::{Command, Output};
+
+    /// Helper: Create a file (and its parent directories) under `root`
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /// Helper: Run `linguist` in `dir` with the given arguments
+    fn run(dir: &Path, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_linguist"))
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .expect("Should run linguist")
+    }
+
+    /// Helper: A repository with a configuration file at its root
+    fn configured_repo(config: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, ".linguist.toml", config);
+        write(root, "src/main.rs", "fn main() {}\n");
+        write(root, "src/app.min.js", "var a=1;\n");
+        write(root, "src/page.inc", "<?php echo 1; ?>\n");
+        write(root, "tools/gen.py", "print('hi')\n");
+        write(
+            root,
+            "third_party/zlib/zlib.c",
+            "int main(void) { return 0; }\n",
+        );
+        dir
+    }
+
+    /// Helper: The languages of a `stats --format json` report, by name
+    fn stats_languages(dir: &Path, args: &[&str]) -> Vec<String> {
+        let output = run(dir, &[&["stats", "--format", "json"], args].concat());
+        assert!(output.status.success(), "{output:?}");
+        let report: Value = serde_json::from_slice(&output.stdout).expect("Should be JSON");
+        let mut names: Vec<String> = report["languages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|l| l["name"].as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn globs_and_overrides_shape_the_breakdown() {
+        let repo = configured_repo(
+            r#"
+include = ["src/", "third_party/"]
+exclude = ["*.min.js"]
+vendor = ["third_party/"]
+
+[extensions]
+".inc" = "php"
+"#,
+        );
+        assert_eq!(stats_languages(repo.path(), &[]), vec!["PHP", "Rust"]);
+        assert_eq!(
+            stats_languages(repo.path(), &["--no-config"]),
+            vec!["PHP", "Python", "Rust", "TOML"]
+        );
+    }
+
+    #[test]
+    fn configuration_is_found_above_the_scanned_directory() {
+        let repo = configured_repo("exclude = [\"main.rs\"]\n");
+        let output = run(repo.path(), &["ls", "src", "-l", "rust"]);
+        assert!(output.status.success());
+        assert!(output.stdout.is_empty());
+
+        let output = run(repo.path(), &["ls", "src", "-l", "rust", "--no-config"]);
+        assert_eq!(output.stdout, b"src/main.rs\n");
+    }
+
+    #[test]
+    fn flags_win_over_configured_defaults() {
+        let repo = configured_repo("format = \"json\"\nmethods = [\"filename\"]\n");
+
+        // Nothing detects main.rs by filename alone
+        let output = run(repo.path(), &["src/main.rs"]);
+        let reports: Value = serde_json::from_slice(&output.stdout).expect("Should be JSON");
+        assert_eq!(reports[0]["detections"], Value::Array(Vec::new()));
+
+        let output = run(repo.path(), &["src/main.rs", "-e", "--format", "text"]);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.starts_with("src/main.rs: "), "{stdout}");
+        assert!(stdout.contains("Rust"), "{stdout}");
+        assert!(stdout.trim_end().ends_with("(by extension)"), "{stdout}");
+    }
+
+    #[test]
+    fn extension_overrides_apply_to_single_files() {
+        let repo = configured_repo("vendor = [\"*.inc\"]\n[extensions]\n\"inc\" = \"PHP\"\n");
+        let output = run(repo.path(), &["src/page.inc", "-e"]);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(stdout, "src/page.inc: PHP (by extension) [vendored]\n");
+    }
+
+    #[test]
+    fn explicit_configuration_file_is_used() {
+        let repo = configured_repo("");
+        write(repo.path(), "other.toml", "include = [\"*.py\"]\n");
+        assert_eq!(
+            stats_languages(repo.path(), &["--config", "other.toml"]),
+            vec!["Python"]
+        );
+    }
+
+    #[test]
+    fn invalid_configurations_fail() {
+        for config in [
+            "formats = \"json\"",
+            "format = \"yaml\"",
+            "methods = [\"magic\"]",
+            "include = [\"src/[a\"]",
+            "vendor = [\"\"]",
+            "[extensions]\n\".inc\" = \"not-a-language\"",
+        ] {
+            let repo = configured_repo(config);
+            let output = run(repo.path(), &["stats"]);
+            assert_eq!(output.status.code(), Some(1), "{config}");
+            let stderr = String::from_utf8(output.stderr).unwrap();
+            assert!(stderr.contains("Error loading configuration"), "{stderr}");
+        }
+    }
+}
@@ -0,0 +1,104 @@
+mod test_detect_reader {
+    use linguist::{detect_reader, detect_reader_with_limit};
+    use std::io::{self, Read};
+
+    /// Reader that fails if more than `budget` bytes are requested from it
+    struct BudgetReader {
+        remaining: usize,
+        budget: usize,
+    }
+
+    impl Read for BudgetReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.remaining == 0 {
+                return Ok(0);
+            }
+            if self.budget == 0 {
+                return Err(io::Error::other("read past the byte limit"));
+            }
+            let n = buf.len().min(self.remaining).min(self.budget);
+            buf[..n].fill(b'x');
+            self.remaining -= n;
+            self.budget -= n;
+            Ok(n)
+        }
+    }
+
+    /// Helper: Assert that the reader based pipeline detects the expected language
+    fn assert_detects(filename: &str, content: &str, expected_language: &str) {
+        let langs = detect_reader(filename, content.as_bytes()).expect("Should not error");
+        let detected_names: Vec<&str> = langs.iter().map(|lang| lang.name).collect();
+        assert!(
+            detected_names.contains(&expected_language),
+            "Expected '{}' to be detected for '{}', but got: {:?}",
+            expected_language,
+            filename,
+            detected_names
+        );
+    }
+
+    #[test]
+    fn detect_unambiguous_extension() {
+        assert_detects("script.py", "", "Python");
+    }
+
+    #[test]
+    fn detect_by_filename() {
+        assert_detects("Makefile", "", "Makefile");
+    }
+
+    #[test]
+    fn detect_ambiguous_extension_by_content() {
+        assert_detects("test.h", "#include <iostream>\nstd::cout << 1;", "C++");
+        assert_detects("test.h", "#include <stdio.h>\nint main() {}", "C");
+    }
+
+    #[test]
+    fn detect_script_by_shebang() {
+        assert_detects("bin/deploy", "#!/usr/bin/env ruby\nputs 1\n", "Ruby");
+        assert_detects("configure", "#!/bin/sh\necho hi\n", "Shell");
+    }
+
+    #[test]
+    fn shebang_does_not_override_extension() {
+        let langs = detect_reader("script.py", "#!/bin/sh\n".as_bytes()).expect("Should not error");
+        assert_eq!(langs[0].name, "Python");
+    }
+
+    #[test]
+    fn unambiguous_files_are_not_read() {
+        let reader = BudgetReader {
+            remaining: 1024,
+            budget: 0,
+        };
+        let langs = detect_reader("script.py", reader).expect("Should not error");
+        assert_eq!(langs[0].name, "Python");
+    }
+
+    #[test]
+    fn reads_no_more_than_limit() {
+        // A huge "file" where only the first 1 KiB may be touched
+        let reader = BudgetReader {
+            remaining: 1 << 30,
+            budget: 1024,
+        };
+        let langs = detect_reader_with_limit("dump.sql", reader, 1024).expect("Should not error");
+        assert!(!langs.is_empty());
+    }
+
+    #[test]
+    fn heuristics_only_see_prefix() {
+        // The C++ marker lies beyond the limit, so the C fallback rule wins
+        let content = format!("{}\n#include <iostream>\n", "/* padding */\n".repeat(100));
+        let langs =
+            detect_reader_with_limit("test.h", content.as_bytes(), 64).expect("Should not error");
+        assert_eq!(langs[0].name, "C");
+    }
+
+    #[test]
+    fn unknown_file_is_empty() {
+        let langs =
+            detect_reader("no-extension-here", "hello".as_bytes()).expect("Should not error");
+        assert!(langs.is_empty());
+    }
+}
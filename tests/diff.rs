@@ -0,0 +1,168 @@
+mod test_diff {
+    use linguist::diff::{LanguageDelta, diff_directories, diff_revisions};
+    use linguist::repository::ScanOptions;
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Helper: Create a file (and its parent directories) under `root`
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /// Helper: Run a git command in `dir`, panicking on failure
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .expect("git should be installed");
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn reports_language_deltas() {
+        let before = tempfile::tempdir().unwrap();
+        let after = tempfile::tempdir().unwrap();
+        write(before.path(), "main.rs", "fn main() {}\n");
+        write(before.path(), "build.py", "print('hi')\n");
+        write(after.path(), "main.rs", "fn main() {}\nfn other() {}\n");
+        write(after.path(), "lib.rs", "pub fn lib() {}\n");
+
+        let changes = diff_directories(before.path(), after.path(), &ScanOptions::default())
+            .expect("Should not error");
+
+        assert_eq!(
+            changes.languages["Rust"],
+            LanguageDelta {
+                bytes: 14 + 16,
+                files: 1
+            }
+        );
+        assert_eq!(
+            changes.languages["Python"],
+            LanguageDelta {
+                bytes: -12,
+                files: -1
+            }
+        );
+        assert!(changes.changed_files.is_empty());
+    }
+
+    #[test]
+    fn unchanged_languages_are_omitted() {
+        let before = tempfile::tempdir().unwrap();
+        let after = tempfile::tempdir().unwrap();
+        write(before.path(), "main.rs", "fn main() {}\n");
+        write(after.path(), "main.rs", "fn main() {}\n");
+
+        let changes = diff_directories(before.path(), after.path(), &ScanOptions::default())
+            .expect("Should not error");
+        assert!(changes.languages.is_empty());
+    }
+
+    #[test]
+    fn lists_files_whose_language_changed() {
+        let before = tempfile::tempdir().unwrap();
+        let after = tempfile::tempdir().unwrap();
+        write(before.path(), "api.h", "#include <stdio.h>\n");
+        write(after.path(), "api.h", "#include <iostream>\n");
+
+        let changes = diff_directories(before.path(), after.path(), &ScanOptions::default())
+            .expect("Should not error");
+
+        assert_eq!(changes.changed_files.len(), 1);
+        assert_eq!(changes.changed_files[0].path, Path::new("api.h"));
+        assert_eq!(changes.changed_files[0].before, Some("C"));
+        assert_eq!(changes.changed_files[0].after, Some("C++"));
+
+        let sorted: Vec<_> = changes
+            .sorted_languages()
+            .iter()
+            .map(|(name, _)| *name)
+            .collect();
+        assert_eq!(sorted, vec!["C++", "C"]);
+    }
+
+    #[test]
+    fn compares_two_revisions() {
+        let repo = tempfile::tempdir().unwrap();
+        let root = repo.path();
+        git(root, &["init", "-q"]);
+        write(root, "main.rs", "fn main() {}\n");
+        write(root, "api.h", "#include <stdio.h>\n");
+        write(root, "build.py", "print('hi')\n");
+        git(root, &["add", "-A"]);
+        git(root, &["commit", "-q", "-m", "first"]);
+
+        write(root, "api.h", "#include <iostream>\n");
+        fs::remove_file(root.join("build.py")).unwrap();
+        git(root, &["add", "-A"]);
+        git(root, &["commit", "-q", "-m", "second"]);
+
+        // Uncommitted changes are not part of either revision
+        write(root, "lib.rs", "pub fn lib() {}\n");
+
+        let changes = diff_revisions(root, "HEAD~1", "HEAD", &ScanOptions::default())
+            .expect("Should not error");
+
+        assert_eq!(
+            changes.languages["Python"],
+            LanguageDelta {
+                bytes: -12,
+                files: -1
+            }
+        );
+        assert_eq!(
+            changes.languages["C++"],
+            LanguageDelta {
+                bytes: 20,
+                files: 1
+            }
+        );
+        assert!(!changes.languages.contains_key("Rust"));
+        assert_eq!(changes.changed_files.len(), 1);
+        assert_eq!(changes.changed_files[0].path, Path::new("api.h"));
+        assert_eq!(changes.changed_files[0].before, Some("C"));
+        assert_eq!(changes.changed_files[0].after, Some("C++"));
+
+        assert!(diff_revisions(root, "HEAD", "does-not-exist", &ScanOptions::default()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn classify_errors_are_reported() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let before = tempfile::tempdir().unwrap();
+        let after = tempfile::tempdir().unwrap();
+        let name = OsStr::from_bytes(b"caf\xe9.rs");
+        for dir in [&before, &after] {
+            write(dir.path(), "main.rs", "fn main() {}\n");
+        }
+        fs::write(after.path().join(name), "fn f() {}\n").unwrap();
+
+        let changes = diff_directories(before.path(), after.path(), &ScanOptions::default())
+            .expect("Should not error");
+        assert!(changes.languages.is_empty());
+        assert!(changes.before_errors.is_empty());
+        assert_eq!(changes.after_errors.len(), 1);
+        assert_eq!(changes.after_errors[0].0.as_os_str(), name);
+
+        // The command line warns about them
+        let output = Command::new(env!("CARGO_BIN_EXE_linguist"))
+            .arg("diff")
+            .arg(before.path())
+            .arg(after.path())
+            .output()
+            .expect("Should run linguist");
+        assert!(output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Warning: Failed to classify"), "{stderr}");
+    }
+}
@@ -1,5 +1,6 @@
 mod test_disambiguate {
-    use linguist::disambiguate;
+    use linguist::{HeuristicLimits, LinguistError, disambiguate, disambiguate_with_limits};
+    use std::time::Duration;
 
     /// Helper: Assert that disambiguation returns the expected language
     fn assert_disambiguates(filename: &str, content: &str, expected_language: &str) {
@@ -357,4 +358,105 @@ namespace MyApp.Business
 }"#;
         assert_disambiguates("test.cs", content, "C#");
     }
+
+    #[test]
+    fn uppercase_extensions_use_the_same_heuristics() {
+        assert_disambiguates("SCRIPT.R", "x <- c(1, 2)\n", "R");
+        assert_disambiguates("WIDGET.H", "#include <vector>\nclass Widget {};\n", "C++");
+        assert_disambiguates("MAIN.RS", "fn main() {}\n", "Rust");
+    }
+
+    /// Helper: A Vim modeline the Vim Help File pattern backtracks on
+    /// exponentially, never finding a `filetype=help`
+    fn hostile_modeline() -> String {
+        format!(" vim:{}x\n", " :".repeat(30))
+    }
+
+    #[test]
+    fn exceeded_backtrack_limit_is_a_non_match() {
+        // The Vim Help File rule gives up, so the .txt fallback applies
+        assert_disambiguates("notes.txt", &hostile_modeline(), "Text");
+
+        let limits = HeuristicLimits::default();
+        let outcome = disambiguate_with_limits("notes.txt", &hostile_modeline(), &limits)
+            .expect("Should not error");
+        assert_eq!(outcome.languages[0].name, "Text");
+        assert_eq!(outcome.warnings.len(), 1);
+        assert!(matches!(
+            outcome.warnings[0],
+            LinguistError::RegexLimitExceeded { .. }
+        ));
+    }
+
+    #[test]
+    fn backtrack_limit_is_configurable() {
+        let content = "{$title}\n";
+        let outcome = disambiguate_with_limits("page.tpl", content, &HeuristicLimits::default())
+            .expect("Should not error");
+        assert_eq!(outcome.languages[0].name, "Smarty");
+        assert!(outcome.warnings.is_empty());
+
+        let limits = HeuristicLimits {
+            backtrack_limit: 1,
+            ..HeuristicLimits::default()
+        };
+        let outcome =
+            disambiguate_with_limits("page.tpl", content, &limits).expect("Should not error");
+        assert!(outcome.languages.is_empty());
+        assert!(!outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn rules_stop_once_the_time_budget_has_passed() {
+        let content = "#include <iostream>\n";
+        let limits = |time_budget| HeuristicLimits {
+            time_budget: Some(time_budget),
+            ..HeuristicLimits::default()
+        };
+
+        let outcome = disambiguate_with_limits("foo.h", content, &limits(Duration::from_secs(60)))
+            .expect("Should not error");
+        assert_eq!(outcome.languages[0].name, "C++");
+        assert!(outcome.warnings.is_empty());
+
+        // Not even the C fallback rule is tried
+        let outcome = disambiguate_with_limits("foo.h", content, &limits(Duration::ZERO))
+            .expect("Should not error");
+        assert!(outcome.languages.is_empty());
+        assert_eq!(
+            outcome.warnings,
+            vec![LinguistError::TimeBudgetExceeded(Duration::ZERO)]
+        );
+    }
+
+    #[test]
+    fn disambiguate_examines_all_of_the_content() {
+        let content = format!(
+            "{}#include <iostream>\n",
+            "// padding\n".repeat(linguist::DEFAULT_READ_LIMIT / 10)
+        );
+        assert!(content.len() > linguist::DEFAULT_READ_LIMIT);
+        assert_disambiguates("foo.h", &content, "C++");
+    }
+
+    #[test]
+    fn content_beyond_the_limit_is_ignored() {
+        let content = format!("// {}\n#include <iostream>\n", "é".repeat(100));
+        let limits = |max_content_len| HeuristicLimits {
+            max_content_len,
+            ..HeuristicLimits::default()
+        };
+
+        let outcome =
+            disambiguate_with_limits("foo.h", &content, &limits(1024)).expect("Should not error");
+        assert_eq!(outcome.languages[0].name, "C++");
+
+        // Without the include, only the C fallback matches. The second limit
+        // cuts a two-byte character in half, which is left out.
+        for max_content_len in [100, 101] {
+            let outcome = disambiguate_with_limits("foo.h", &content, &limits(max_content_len))
+                .expect("Should not error");
+            assert_eq!(outcome.languages[0].name, "C", "{max_content_len}");
+        }
+    }
 }
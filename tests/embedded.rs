@@ -0,0 +1,155 @@
+mod test_embedded {
+    use linguist::embedded::{find_regions, has_regions};
+    use linguist::repository::{ScanOptions, analyze};
+    use std::fs;
+
+    /// Helper: The (language, text) of each region found in content
+    fn regions<'a>(language: &str, content: &'a str) -> Vec<(&'static str, &'a str)> {
+        find_regions(language, content)
+            .into_iter()
+            .map(|region| (region.language, &content[region.range]))
+            .collect()
+    }
+
+    #[test]
+    fn vue_blocks_use_lang_attributes() {
+        let content = r#"<template lang="pug">
+div {{ msg }}
+</template>
+
+<script setup lang="ts">
+const msg: string = "hi"
+</script>
+
+<style scoped lang='scss'>
+$c: red;
+</style>
+"#;
+        assert_eq!(
+            regions("Vue", content),
+            vec![
+                ("Pug", "\ndiv {{ msg }}\n"),
+                ("TypeScript", "\nconst msg: string = \"hi\"\n"),
+                ("SCSS", "\n$c: red;\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn vue_templates_without_lang_are_not_regions() {
+        let content = "<template><div></div></template>\n<script>export default {}</script>\n";
+        assert_eq!(
+            regions("Vue", content),
+            vec![("JavaScript", "export default {}")]
+        );
+    }
+
+    #[test]
+    fn html_blocks_default_to_javascript_and_css() {
+        let content = r#"<html>
+<head>
+<STYLE>body { color: red }</STYLE>
+<script src="app.js"></script>
+<script src="lib.js" />
+<script type="module">import "./app.js"</script>
+<script type="application/ld+json">{"@type": "Thing"}</script>
+<script type="text/typescript">let x: number = 1</script>
+<script type="text/x-unknown-thing">ignored</script>
+</head>
+</html>
+"#;
+        assert_eq!(
+            regions("HTML", content),
+            vec![
+                ("CSS", "body { color: red }"),
+                ("JavaScript", "import \"./app.js\""),
+                ("JSON", "{\"@type\": \"Thing\"}"),
+                ("TypeScript", "let x: number = 1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn html_comments_and_quoted_attributes_are_skipped() {
+        let content = r#"<!-- <script>commented()</script> -->
+<script data-note="a > b">real()</script>
+<template><style>p {}</style></template>
+"#;
+        assert_eq!(
+            regions("HTML", content),
+            vec![("JavaScript", "real()"), ("CSS", "p {}")]
+        );
+    }
+
+    #[test]
+    fn unclosed_blocks_run_to_the_end() {
+        let content = "<p>hi</p><script>let x = 1;\n";
+        assert_eq!(
+            regions("Svelte", content),
+            vec![("JavaScript", "let x = 1;\n")]
+        );
+    }
+
+    #[test]
+    fn markdown_fences_use_info_strings() {
+        let content = r#"# Title
+
+```rust
+fn main() {}
+```
+
+~~~~ {.python}
+print(1)
+```
+still python
+~~~~
+
+```
+no language
+```
+
+```not-a-language
+unknown
+```
+
+```markdown
+same language
+```
+
+``` sh
+echo unclosed
+"#;
+        assert_eq!(
+            regions("Markdown", content),
+            vec![
+                ("Rust", "fn main() {}\n"),
+                ("Python", "print(1)\n```\nstill python\n"),
+                ("Shell", "echo unclosed\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn other_languages_have_no_regions() {
+        assert!(has_regions("Vue"));
+        assert!(!has_regions("Rust"));
+        assert!(regions("Rust", "<script>let x = 1;</script>").is_empty());
+    }
+
+    #[test]
+    fn breakdown_attributes_region_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = "<template><p>hi</p></template>\n<script lang=\"ts\">let x = 1;</script>\n";
+        fs::write(dir.path().join("App.vue"), content).unwrap();
+
+        let options = ScanOptions {
+            embedded_languages: true,
+            ..ScanOptions::default()
+        };
+        let breakdown = analyze(dir.path(), &options).expect("Should not error");
+        assert_eq!(breakdown.languages["TypeScript"].bytes, 10);
+        assert_eq!(breakdown.languages["Vue"].bytes, content.len() as u64 - 10);
+        assert_eq!(breakdown.languages["Vue"].files, 1);
+        assert_eq!(breakdown.languages["TypeScript"].files, 0);
+    }
+}
@@ -0,0 +1,142 @@
+mod test_files_from {
+    use serde_json::Value;
+    use std::io::{BufRead, BufReader, Write};
+    use std::process::{Command, Output, Stdio};
+
+    /// Helper: Run `linguist` with the given arguments and input piped to stdin
+    fn run(args: &[&str], input: &[u8]) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_linguist"))
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Should start linguist");
+        child.stdin.take().unwrap().write_all(input).unwrap();
+        child.wait_with_output().unwrap()
+    }
+
+    /// Helper: Write files into a temporary directory, returning their paths
+    fn files(dir: &tempfile::TempDir, files: &[(&str, &str)]) -> Vec<String> {
+        files
+            .iter()
+            .map(|(name, content)| {
+                let path = dir.path().join(name);
+                std::fs::write(&path, content).unwrap();
+                path.to_str().unwrap().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn newline_separated_list_from_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = files(&dir, &[("a.py", "print(1)\n"), ("b.rb", "puts 1\n")]);
+        let list = format!("{}\r\n\n{}\n", paths[0], paths[1]);
+
+        let output = run(&["--files-from", "-", "--by-extension"], list.as_bytes());
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(
+            stdout,
+            format!(
+                "{}: Python (by extension) \n{}: Ruby (by extension) \n",
+                paths[0], paths[1]
+            )
+        );
+    }
+
+    #[test]
+    fn nul_separated_list_allows_newlines_in_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = files(
+            &dir,
+            &[("odd\nname.py", "print(1)\n"), ("c.go", "package c\n")],
+        );
+        let list = format!("{}\0{}\0", paths[0], paths[1]);
+
+        let output = run(
+            &["--format", "json", "--files-from", "-", "-0"],
+            list.as_bytes(),
+        );
+        assert!(output.status.success());
+        let reports: Vec<Value> = serde_json::from_slice(&output.stdout).expect("Should be JSON");
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0]["path"], paths[0]);
+        assert_eq!(reports[0]["detections"][0]["languages"][0], "Python");
+        assert_eq!(reports[1]["detections"][0]["languages"][0], "Go");
+    }
+
+    #[test]
+    fn list_file_follows_file_arguments() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = files(&dir, &[("a.py", ""), ("b.rb", "")]);
+        let list = dir.path().join("list.txt");
+        std::fs::write(&list, format!("{}\n", paths[1])).unwrap();
+
+        let output = run(
+            &[
+                "--format",
+                "json",
+                "--files-from",
+                list.to_str().unwrap(),
+                &paths[0],
+            ],
+            b"",
+        );
+        assert!(output.status.success());
+        let reports: Vec<Value> = serde_json::from_slice(&output.stdout).expect("Should be JSON");
+        let reported: Vec<_> = reports.iter().map(|r| r["path"].clone()).collect();
+        assert_eq!(reported, vec![paths[0].clone(), paths[1].clone()]);
+    }
+
+    #[test]
+    fn json_output_matches_file_arguments() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = files(&dir, &[("a.py", "print(1)\n"), ("b.h", "class B {};\n")]);
+
+        let from_list = run(
+            &["--format", "json", "--files-from", "-"],
+            paths.join("\n").as_bytes(),
+        );
+        let from_args = run(&["--format", "json", &paths[0], &paths[1]], b"");
+        assert_eq!(from_list.stdout, from_args.stdout);
+
+        let empty = run(&["--format", "json", "--files-from", "-"], b"");
+        assert_eq!(String::from_utf8(empty.stdout).unwrap(), "[]\n");
+    }
+
+    #[test]
+    fn output_starts_before_the_list_ends() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = files(&dir, &[("a.py", "")]);
+
+        let mut child = Command::new(env!("CARGO_BIN_EXE_linguist"))
+            .args(["--files-from", "-", "--by-extension"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Should start linguist");
+        let mut stdin = child.stdin.take().unwrap();
+        writeln!(stdin, "{}", paths[0]).unwrap();
+
+        // The list is still open, so this only returns if output is streamed
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        assert!(line.contains("Python (by extension)"));
+
+        drop(stdin);
+        assert!(child.wait().unwrap().success());
+    }
+
+    #[test]
+    fn stdin_cannot_be_content_and_list() {
+        let output = run(&["--files-from", "-", "-"], b"");
+        assert!(!output.status.success());
+
+        let output = run(&["--null", "Cargo.toml"], b"");
+        assert!(!output.status.success());
+    }
+}
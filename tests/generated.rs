@@ -0,0 +1,49 @@
+mod test_generated {
+    use linguist::generated::{is_binary, is_generated};
+    use linguist::repository::{ScanOptions, classify_reader};
+
+    #[test]
+    fn generated_by_file_name() {
+        assert!(is_generated("package-lock.json", b"{}"));
+        assert!(is_generated("proto/user_pb2.py", b""));
+        assert!(is_generated("static/app.js.map", b""));
+        assert!(!is_generated("src/lock.rs", b""));
+        assert!(!is_generated(".pb.go", b""));
+    }
+
+    #[test]
+    fn generated_by_marker_in_first_lines() {
+        let go = "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n";
+        assert!(is_generated("api/user.go", go.as_bytes()));
+
+        let late = format!("{}// @generated\n", "// license\n".repeat(20));
+        assert!(!is_generated("src/lib.rs", late.as_bytes()));
+    }
+
+    #[test]
+    fn classified_files_are_flagged() {
+        let content = "// Code generated by stringer. DO NOT EDIT.\npackage api\n";
+        let classify = |path: &str, generated_markers| {
+            let options = ScanOptions {
+                generated_markers,
+                ..ScanOptions::default()
+            };
+            classify_reader(path.as_ref(), 0, content.as_bytes(), &options)
+                .expect("Should not error")
+                .generated
+        };
+
+        assert!(classify("go.sum", false));
+        assert!(!classify("api/kind_string.go", false));
+        assert!(classify("api/kind_string.go", true));
+    }
+
+    #[test]
+    fn binary_content() {
+        assert!(is_binary(b"GIF89a\x01\x00\x01\x00\x00"));
+        assert!(is_binary(&[0x01; 100]));
+        assert!(!is_binary(b""));
+        assert!(!is_binary(b"\x1b[1mbold\x1b[0m\n\tindented\r\n"));
+        assert!(!is_binary("na\u{ef}ve caf\u{e9}".as_bytes()));
+    }
+}
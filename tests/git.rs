@@ -0,0 +1,158 @@
+mod test_git {
+    use linguist::git::{analyze_revision, list_tree};
+    use linguist::repository::{ScanOptions, analyze};
+    use std::fs;
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Helper: Run a git command in `dir`, panicking on failure
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .status()
+            .expect("git should be installed");
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    /// Helper: Create a file (and its parent directories) under `root`
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /// Helper: Initialise a repository with two commits
+    fn sample_repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "-q"]);
+
+        write(root, "src/main.rs", "fn main() {}\n");
+        write(root, "include/api.h", "#include <iostream>\n");
+        git(root, &["add", "-A"]);
+        git(root, &["commit", "-q", "-m", "first"]);
+
+        write(root, "scripts/build.py", "print('hi')\n");
+        write(root, "third_party/lib.c", "int x;\n");
+        write(root, "legacy/util.inc", "<?php echo 1;\n");
+        write(
+            root,
+            ".gitattributes",
+            "third_party/** linguist-vendored\n*.inc linguist-language=PHP\n",
+        );
+        git(root, &["add", "-A"]);
+        git(root, &["commit", "-q", "-m", "second"]);
+        dir
+    }
+
+    #[test]
+    fn list_tree_of_older_revision() {
+        let repo = sample_repo();
+        let paths: Vec<_> = list_tree(repo.path(), "HEAD~1")
+            .expect("Should not error")
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![Path::new("include/api.h"), Path::new("src/main.rs")]
+        );
+    }
+
+    #[test]
+    fn revision_matches_working_tree() {
+        let repo = sample_repo();
+        let options = ScanOptions {
+            count_lines: true,
+            ..ScanOptions::default()
+        };
+        let from_git = analyze_revision(repo.path(), "HEAD", &options).expect("Should not error");
+        let from_disk = analyze(repo.path(), &options).expect("Should not error");
+
+        assert_eq!(from_git.languages, from_disk.languages);
+        assert_eq!(from_git.languages["C++"].files, 1);
+    }
+
+    #[test]
+    fn parallel_revision_scans_count_every_file() {
+        let repo = sample_repo();
+        for i in 0..20 {
+            write(
+                repo.path(),
+                &format!("src/parts/mod{i}.rs"),
+                "fn f() {}\n\nfn g() {}\n",
+            );
+        }
+        git(repo.path(), &["add", "-A"]);
+        git(repo.path(), &["commit", "-q", "-m", "third"]);
+
+        let options = |jobs| ScanOptions {
+            count_lines: true,
+            embedded_languages: true,
+            jobs,
+            ..ScanOptions::default()
+        };
+        let serial = analyze_revision(repo.path(), "HEAD", &options(1)).expect("Should not error");
+        let parallel =
+            analyze_revision(repo.path(), "HEAD", &options(8)).expect("Should not error");
+
+        assert_eq!(parallel.languages, serial.languages);
+        assert_eq!(parallel.languages["Rust"].files, 21);
+    }
+
+    #[test]
+    fn gitattributes_from_revision_are_applied() {
+        let repo = sample_repo();
+        let breakdown = analyze_revision(repo.path(), "HEAD", &ScanOptions::default())
+            .expect("Should not error");
+
+        assert!(!breakdown.languages.contains_key("C"));
+        assert_eq!(breakdown.languages["PHP"].files, 1);
+    }
+
+    #[test]
+    fn bare_repository() {
+        let repo = sample_repo();
+        let bare = tempfile::tempdir().unwrap();
+        git(
+            bare.path(),
+            &["clone", "-q", "--bare", repo.path().to_str().unwrap(), "."],
+        );
+
+        let breakdown = analyze_revision(bare.path(), "HEAD~1", &ScanOptions::default())
+            .expect("Should not error");
+        assert_eq!(breakdown.files.len(), 2);
+        assert_eq!(breakdown.languages["Rust"].files, 1);
+    }
+
+    #[test]
+    fn unknown_revision_is_an_error() {
+        let repo = sample_repo();
+        assert!(analyze_revision(repo.path(), "does-not-exist", &ScanOptions::default()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_path_is_a_file_error() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let repo = sample_repo();
+        let name = OsStr::from_bytes(b"src/caf\xe9.rs");
+        fs::write(repo.path().join(name), "fn f() {}\n").unwrap();
+        git(repo.path(), &["add", "-A"]);
+        git(repo.path(), &["commit", "-q", "-m", "third"]);
+
+        let entries = list_tree(repo.path(), "HEAD").expect("Should not error");
+        assert!(entries.iter().any(|entry| entry.path.as_os_str() == name));
+
+        let breakdown = analyze_revision(repo.path(), "HEAD", &ScanOptions::default())
+            .expect("Should not error");
+        assert_eq!(breakdown.errors.len(), 1);
+        assert_eq!(breakdown.errors[0].0.as_os_str(), name);
+        assert_eq!(breakdown.languages["Rust"].files, 1);
+    }
+}
@@ -0,0 +1,56 @@
+mod test_gitattributes {
+    use linguist::gitattributes::GitAttributes;
+
+    #[test]
+    fn pattern_without_slash_matches_at_any_depth() {
+        let mut attributes = GitAttributes::new();
+        attributes.add_file("", "*.inc linguist-language=PHP");
+        assert_eq!(attributes.language("util.inc"), Some("PHP"));
+        assert_eq!(attributes.language("a/b/util.inc"), Some("PHP"));
+        assert_eq!(attributes.language("util.c"), None);
+    }
+
+    #[test]
+    fn pattern_with_slash_is_anchored() {
+        let mut attributes = GitAttributes::new();
+        attributes.add_file("", "/lib/*.js linguist-vendored");
+        assert_eq!(attributes.vendored("lib/jquery.js"), Some(true));
+        assert_eq!(attributes.vendored("src/lib/jquery.js"), None);
+        assert_eq!(attributes.vendored("lib/nested/jquery.js"), None);
+    }
+
+    #[test]
+    fn nested_files_are_relative_to_their_directory() {
+        let mut attributes = GitAttributes::new();
+        attributes.add_file("", "*.js linguist-vendored");
+        attributes.add_file("app", "src/*.js -linguist-vendored");
+        assert_eq!(attributes.vendored("app/src/main.js"), Some(false));
+        assert_eq!(attributes.vendored("src/main.js"), Some(true));
+    }
+
+    #[test]
+    fn later_rules_take_precedence() {
+        let mut attributes = GitAttributes::new();
+        attributes.add_file("", "*.h linguist-language=C\n*.h linguist-language=C++");
+        assert_eq!(attributes.language("api.h"), Some("C++"));
+    }
+
+    #[test]
+    fn languages_resolve_through_aliases() {
+        let mut attributes = GitAttributes::new();
+        attributes.add_file(
+            "",
+            "*.l linguist-language=common-lisp\n*.x linguist-language=nope",
+        );
+        assert_eq!(attributes.language("init.l"), Some("Common Lisp"));
+        assert_eq!(attributes.language("file.x"), None);
+    }
+
+    #[test]
+    fn unrelated_attributes_and_comments_are_ignored() {
+        let mut attributes = GitAttributes::new();
+        attributes.add_file("", "# comment\n*.sh text eol=lf\n*.png binary");
+        assert_eq!(attributes.vendored("run.sh"), None);
+        assert_eq!(attributes.language("run.sh"), None);
+    }
+}
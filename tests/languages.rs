@@ -0,0 +1,107 @@
+mod test_languages {
+    use serde_json::Value;
+    use std::process::{Command, Output};
+
+    /// Helper: Run `linguist` with the given arguments
+    fn run(args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_linguist"))
+            .args(args)
+            .output()
+            .expect("Should run linguist")
+    }
+
+    /// Helper: Run with `--format json`, returning the parsed output
+    fn run_json(args: &[&str]) -> Value {
+        let output = run(&[args, &["--format", "json"]].concat());
+        assert!(output.status.success());
+        serde_json::from_slice(&output.stdout).expect("Should be JSON")
+    }
+
+    /// Helper: The names of the languages in a JSON list
+    fn names(languages: &Value) -> Vec<&str> {
+        languages
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|l| l["name"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn list_by_extension() {
+        let with_dot = run_json(&["languages", "--extension", ".m"]);
+        let names = names(&with_dot);
+        assert!(names.contains(&"Objective-C"));
+        assert!(names.contains(&"MATLAB"));
+        assert!(!names.contains(&"C"));
+
+        let without_dot = run_json(&["languages", "--extension", "M"]);
+        assert_eq!(with_dot, without_dot);
+    }
+
+    #[test]
+    fn filters_combine() {
+        let languages = run_json(&[
+            "languages",
+            "--interpreter",
+            "python3",
+            "--type",
+            "programming",
+        ]);
+        assert_eq!(names(&languages), vec!["Python"]);
+
+        let languages = run_json(&["languages", "--interpreter", "python3", "--type", "data"]);
+        assert_eq!(names(&languages), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn list_by_group_name_or_alias() {
+        let languages = run_json(&["languages", "--group", "ts"]);
+        let names = names(&languages);
+        assert!(names.contains(&"TypeScript"));
+        assert!(names.contains(&"TSX"));
+        assert!(!names.contains(&"JavaScript"));
+    }
+
+    #[test]
+    fn text_list_is_sorted_names_and_types() {
+        let output = run(&["languages", "--type", "prose"]);
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let names: Vec<_> = stdout
+            .lines()
+            .map(|line| {
+                assert!(line.ends_with("  prose"), "{line}");
+                line.trim_end_matches("prose").trim()
+            })
+            .collect();
+        assert!(names.contains(&"Markdown"));
+
+        let mut sorted = names.clone();
+        sorted.sort_by_key(|name| name.to_lowercase());
+        assert_eq!(names, sorted);
+    }
+
+    #[test]
+    fn info_prints_the_full_record() {
+        let language = run_json(&["info", "cpp"]);
+        assert_eq!(language["name"], "C++");
+        assert_eq!(language["type"], "programming");
+        assert_eq!(language["tm_scope"], "source.c++");
+        assert_eq!(language["aliases"][0], "cpp");
+        assert_eq!(language["language_id"], 43);
+
+        let output = run(&["info", "Elixir"]);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("Color:                #6e4a7e"));
+        assert!(stdout.contains("TextMate scope:       source.elixir"));
+    }
+
+    #[test]
+    fn info_of_unknown_language_fails() {
+        let output = run(&["info", "not-a-language"]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("Unknown language not-a-language"));
+    }
+}
@@ -0,0 +1,94 @@
+mod test_lines {
+    use linguist::lines::{LineCounts, count_lines};
+
+    /// Helper: Assert the (code, comment, blank) counts of content in a language
+    fn assert_counts(language: &str, content: &str, expected: (u64, u64, u64)) {
+        let counts = count_lines(content.as_bytes(), language).expect("Should not error");
+        assert_eq!(
+            counts,
+            LineCounts {
+                code: expected.0,
+                comment: expected.1,
+                blank: expected.2,
+            },
+            "Unexpected counts for {} content:\n{}",
+            language,
+            content
+        );
+    }
+
+    #[test]
+    fn c_style_comments() {
+        let content = r#"
+// line comment
+int main() { /* inline */ return 0; }
+/*
+ * block
+ */
+
+int x; // trailing
+"#;
+        assert_counts("C", content, (2, 4, 2));
+    }
+
+    #[test]
+    fn nested_block_comments() {
+        let content = "/* outer\n/* inner */\nstill comment */\nfn main() {}\n";
+        assert_counts("Rust", content, (1, 3, 0));
+    }
+
+    #[test]
+    fn unnested_block_comments_end_at_first_close() {
+        let content = "/* outer\n/* inner */\nint x;\n";
+        assert_counts("C", content, (1, 2, 0));
+    }
+
+    #[test]
+    fn comment_markers_inside_strings_are_code() {
+        let content =
+            "let url = \"http://example.com\";\nlet s = \"/* not a comment\";\nlet y = 1;\n";
+        assert_counts("Rust", content, (3, 0, 0));
+    }
+
+    #[test]
+    fn escaped_quotes_do_not_end_strings() {
+        let content = "s = \"a \\\" # still string\"\n# comment\n";
+        assert_counts("Python", content, (1, 1, 0));
+    }
+
+    #[test]
+    fn multiline_strings_are_code() {
+        let content = "x = \"\"\"\n# not a comment\n\"\"\"\n# comment\n";
+        assert_counts("Python", content, (3, 1, 0));
+    }
+
+    #[test]
+    fn single_line_strings_end_at_end_of_line() {
+        // An apostrophe in plain YAML must not swallow the following lines
+        let content = "title: don't\n# comment\nkey: value\n";
+        assert_counts("YAML", content, (2, 1, 0));
+    }
+
+    #[test]
+    fn block_markers_sharing_line_prefix() {
+        let content = "--[[\nblock\n]]\n-- line\nprint(1)\n";
+        assert_counts("Lua", content, (1, 4, 0));
+    }
+
+    #[test]
+    fn unknown_syntax_counts_blank_and_code() {
+        let content = "# heading\n\nSome text\n";
+        assert_counts("Markdown", content, (2, 0, 1));
+    }
+
+    #[test]
+    fn last_line_without_newline() {
+        assert_counts("Rust", "fn main() {}\n// end", (1, 1, 0));
+    }
+
+    #[test]
+    fn total() {
+        let counts = count_lines("a\n\n// b\n".as_bytes(), "Rust").expect("Should not error");
+        assert_eq!(counts.total(), 3);
+    }
+}
@@ -0,0 +1,125 @@
+mod test_ls {
+    use std::fs;
+    use std::path::Path;
+    use std::process::{Command, Output};
+
+    /// Helper: Run `linguist ls` on a directory with the given arguments
+    fn ls(root: &Path, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_linguist"))
+            .arg("ls")
+            .arg(root)
+            .args(args)
+            .output()
+            .expect("Should run linguist")
+    }
+
+    /// Helper: The listed paths, relative to the root
+    fn listed(root: &Path, args: &[&str]) -> Vec<String> {
+        let output = ls(root, args);
+        assert!(output.status.success());
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let path = Path::new(line).strip_prefix(root).unwrap();
+                path.to_string_lossy().replace('\\', "/")
+            })
+            .collect()
+    }
+
+    /// Helper: A tree with files whose language isn't given by their extension
+    fn sample_tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            ("app.rb", "puts 1\n"),
+            ("bin/deploy", "#!/usr/bin/env ruby\nputs 'deploying'\n"),
+            ("bin/setup", "#!/bin/sh\necho hi\n"),
+            ("include/widget.h", "#include <vector>\nclass Widget {};\n"),
+            ("src/view.tsx", "export const View = () => <div />;\n"),
+            ("src/model.ts", "export type Model = {};\n"),
+            (
+                "src/schema.ts",
+                "// @generated by codegen\nexport type Schema = {};\n",
+            ),
+            ("vendor/gems/lib.rb", "puts 2\n"),
+        ];
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn shebang_scripts_are_listed() {
+        let dir = sample_tree();
+        assert_eq!(
+            listed(dir.path(), &["--language", "ruby"]),
+            vec!["app.rb", "bin/deploy"]
+        );
+        assert_eq!(listed(dir.path(), &["-l", "sh"]), vec!["bin/setup"]);
+    }
+
+    #[test]
+    fn ambiguous_extensions_use_the_detected_language() {
+        let dir = sample_tree();
+        assert_eq!(listed(dir.path(), &["-l", "c++"]), vec!["include/widget.h"]);
+        assert!(listed(dir.path(), &["-l", "c"]).is_empty());
+    }
+
+    #[test]
+    fn groups_and_several_languages_are_matched() {
+        let dir = sample_tree();
+        assert_eq!(
+            listed(dir.path(), &["-l", "typescript"]),
+            vec!["src/model.ts", "src/view.tsx"]
+        );
+        assert_eq!(
+            listed(dir.path(), &["-l", "shell,c++", "-l", "tsx"]),
+            vec!["bin/setup", "include/widget.h", "src/view.tsx"]
+        );
+    }
+
+    #[test]
+    fn vendored_files_are_excluded_unless_requested() {
+        let dir = sample_tree();
+        assert_eq!(
+            listed(dir.path(), &["-l", "ruby", "--include-vendored"]),
+            vec!["app.rb", "bin/deploy", "vendor/gems/lib.rb"]
+        );
+    }
+
+    #[test]
+    fn generated_files_are_excluded_unless_requested() {
+        let dir = sample_tree();
+        assert_eq!(
+            listed(dir.path(), &["-l", "typescript"]),
+            vec!["src/model.ts", "src/view.tsx"]
+        );
+        assert_eq!(
+            listed(dir.path(), &["-l", "typescript", "--include-generated"]),
+            vec!["src/model.ts", "src/schema.ts", "src/view.tsx"]
+        );
+    }
+
+    #[test]
+    fn paths_can_be_nul_separated() {
+        let dir = sample_tree();
+        let output = ls(dir.path(), &["-l", "ruby", "-0"]);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let paths: Vec<_> = stdout.split_terminator('\0').collect();
+        assert_eq!(paths.len(), 2);
+        assert!(paths[1].ends_with("deploy"));
+        assert!(!stdout.contains('\n'));
+    }
+
+    #[test]
+    fn unknown_languages_fail() {
+        let dir = sample_tree();
+        let output = ls(dir.path(), &["-l", "ruby,not-a-language"]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("Unknown language not-a-language"));
+    }
+}
@@ -0,0 +1,161 @@
+mod test_notebook {
+    use linguist::notebook::{NOTEBOOK_LANGUAGE, inspect_notebook};
+    use linguist::repository::{ScanOptions, analyze, classify_reader};
+    use linguist::{DEFAULT_READ_LIMIT, LinguistError, detect_reader};
+    use std::fs;
+
+    /// Helper: Build a notebook with the given metadata and code cell sources
+    fn notebook(metadata: &str, sources: &[&str]) -> String {
+        let cells: Vec<String> = sources
+            .iter()
+            .map(|source| {
+                format!(
+                    r#"{{"cell_type": "code", "source": {source}, "outputs": [{{"output_type": "stream", "text": "ignored"}}]}}"#
+                )
+            })
+            .chain([r##"{"cell_type": "markdown", "source": "# Title"}"##.to_string()])
+            .collect();
+        format!(
+            r#"{{"cells": [{}], "metadata": {metadata}, "nbformat": 4, "nbformat_minor": 5}}"#,
+            cells.join(", ")
+        )
+    }
+
+    /// Helper: Inspect a notebook, returning its language name and code size
+    fn inspect(content: &str) -> (Option<&'static str>, u64) {
+        let info = inspect_notebook(content.as_bytes()).expect("Should not error");
+        (info.language.map(|l| l.name), info.code_bytes)
+    }
+
+    #[test]
+    fn kernelspec_language_is_preferred() {
+        let content = notebook(
+            r#"{"kernelspec": {"language": "R", "name": "ir"}, "language_info": {"name": "python"}}"#,
+            &[r#""x <- 1""#],
+        );
+        assert_eq!(inspect(&content).0, Some("R"));
+    }
+
+    #[test]
+    fn language_info_is_used_without_kernelspec() {
+        let content = notebook(r#"{"language_info": {"name": "julia"}}"#, &[]);
+        assert_eq!(inspect(&content).0, Some("Julia"));
+    }
+
+    #[test]
+    fn languages_are_resolved_through_aliases() {
+        let content = notebook(
+            r#"{"kernelspec": {"language": "python3", "name": "python3"}}"#,
+            &[],
+        );
+        assert_eq!(inspect(&content).0, Some("Python"));
+
+        let content = notebook(r#"{"kernelspec": {"language": "not-a-language"}}"#, &[]);
+        assert_eq!(inspect(&content).0, None);
+    }
+
+    #[test]
+    fn code_bytes_count_only_code_cells() {
+        let content = notebook(
+            r#"{"kernelspec": {"language": "python"}}"#,
+            &[r#""import os""#, r#"["x = 1\n", "y = 2"]"#],
+        );
+        assert_eq!(inspect(&content), (Some("Python"), 9 + 6 + 5));
+    }
+
+    #[test]
+    fn cell_type_may_follow_the_source() {
+        let content = r#"{"cells": [{"source": "x = 1", "cell_type": "code"}, {"source": ["Notes"], "cell_type": "markdown"}], "metadata": {}}"#;
+        assert_eq!(inspect(content), (None, 5));
+    }
+
+    #[test]
+    fn invalid_notebooks_are_errors() {
+        let result = inspect_notebook("not json".as_bytes());
+        assert!(matches!(result, Err(LinguistError::InvalidNotebook(_))));
+
+        let nested = r#"{"cells": [{"cell_type": "code", "source": [["x"]]}]}"#;
+        let result = inspect_notebook(nested.as_bytes());
+        assert!(matches!(result, Err(LinguistError::InvalidNotebook(_))));
+    }
+
+    #[test]
+    fn embedded_bytes_are_attributed_to_the_kernel_language() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = notebook(
+            r#"{"kernelspec": {"language": "python"}}"#,
+            &[r#""print(1)""#],
+        );
+        fs::write(dir.path().join("analysis.ipynb"), &content).unwrap();
+
+        let breakdown = analyze(dir.path(), &ScanOptions::default()).expect("Should not error");
+        assert_eq!(
+            breakdown.languages[NOTEBOOK_LANGUAGE].bytes,
+            content.len() as u64
+        );
+        assert!(!breakdown.languages.contains_key("Python"));
+
+        let options = ScanOptions {
+            embedded_languages: true,
+            ..ScanOptions::default()
+        };
+        let breakdown = analyze(dir.path(), &options).expect("Should not error");
+        assert_eq!(breakdown.languages["Python"].bytes, 8);
+        assert_eq!(
+            breakdown.languages[NOTEBOOK_LANGUAGE].bytes,
+            content.len() as u64 - 8
+        );
+        assert_eq!(breakdown.total_bytes(), content.len() as u64);
+    }
+
+    #[test]
+    fn detect_reader_reports_the_kernel_language() {
+        let content = notebook(
+            r#"{"kernelspec": {"language": "julia", "name": "julia-1.10"}}"#,
+            &[r#""println(1)""#],
+        );
+        let languages = detect_reader("analysis.ipynb", content.as_bytes()).unwrap();
+        assert_eq!(languages.len(), 1);
+        assert_eq!(languages[0].name, NOTEBOOK_LANGUAGE);
+        assert_eq!(languages[0].embedded, Some("Julia"));
+
+        // Metadata comes after the cells, beyond the read limit of large notebooks
+        let output = format!(r#""{}""#, "x".repeat(DEFAULT_READ_LIMIT));
+        let content = notebook(r#"{"language_info": {"name": "python"}}"#, &[&output]);
+        let languages = detect_reader("large.ipynb", content.as_bytes()).unwrap();
+        assert_eq!(languages[0].embedded, Some("Python"));
+
+        // Notebooks without a known kernel language, or that aren't notebooks
+        let content = notebook("{}", &[r#""1""#]);
+        let languages = detect_reader("empty.ipynb", content.as_bytes()).unwrap();
+        assert_eq!(languages[0].embedded, None);
+        let languages = detect_reader("broken.ipynb", "{".as_bytes()).unwrap();
+        assert_eq!(languages[0].name, NOTEBOOK_LANGUAGE);
+        assert_eq!(languages[0].embedded, None);
+
+        let languages = detect_reader("main.py", "print(1)\n".as_bytes()).unwrap();
+        assert_eq!(languages[0].embedded, None);
+    }
+
+    #[test]
+    fn scans_only_read_notebooks_when_asked_to() {
+        /// Helper: A reader failing on every read
+        struct Unreadable;
+
+        impl std::io::Read for Unreadable {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("should not be read"))
+            }
+        }
+
+        let entry = classify_reader(
+            "analysis.ipynb".as_ref(),
+            0,
+            Unreadable,
+            &ScanOptions::default(),
+        )
+        .expect("Should not error");
+        assert_eq!(entry.language, Some(NOTEBOOK_LANGUAGE));
+        assert!(detect_reader("analysis.ipynb", Unreadable).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+mod test_policy {
+    use linguist::LinguistError;
+    use linguist::policy::{Policy, Violation};
+    use linguist::repository::{Breakdown, FileEntry};
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    /// Helper: A classified file
+    fn file(path: &str, language: &'static str, size: u64, vendored: bool) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from(path),
+            size,
+            language: Some(language),
+            vendored,
+            documentation: false,
+            generated: false,
+            lines: None,
+            embedded: Vec::new(),
+        }
+    }
+
+    /// Helper: Check a breakdown of the given files against a policy
+    fn check(policy: &str, files: Vec<FileEntry>) -> Vec<Violation> {
+        let policy = Policy::from_yaml(policy).expect("Should parse");
+        policy.check(&Breakdown::from_entries(files))
+    }
+
+    #[test]
+    fn denied_languages_are_reported() {
+        let violations = check(
+            "deny: [perl]",
+            vec![
+                file("src/main.rs", "Rust", 10, false),
+                file("tools/gen.pl", "Perl", 10, false),
+            ],
+        );
+        assert_eq!(
+            violations,
+            vec![Violation::DeniedLanguage {
+                path: PathBuf::from("tools/gen.pl"),
+                language: "Perl",
+                rule: None,
+            }]
+        );
+        assert_eq!(
+            violations[0].to_string(),
+            "tools/gen.pl: Perl is denied by the policy"
+        );
+    }
+
+    #[test]
+    fn allow_lists_restrict_code_but_not_data() {
+        let violations = check(
+            "allow: [Rust]",
+            vec![
+                file("src/main.rs", "Rust", 10, false),
+                file("Cargo.toml", "TOML", 10, false),
+                file("README.md", "Markdown", 10, false),
+                file("build.py", "Python", 10, false),
+            ],
+        );
+        assert_eq!(violations.len(), 1);
+        assert!(matches!(
+            violations[0],
+            Violation::UnlistedLanguage {
+                language: "Python",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn path_rules_override_global_lists() {
+        let policy = r#"
+allow: [Rust, Python, Shell]
+deny: [Perl]
+paths:
+  - glob: "scripts/**"
+    allow: [Shell]
+  - glob: "legacy/**"
+    allow: [Perl]
+"#;
+        let violations = check(
+            policy,
+            vec![
+                file("legacy/old.pl", "Perl", 10, false),
+                file("scripts/build.py", "Python", 10, false),
+                file("scripts/build.sh", "Shell", 10, false),
+                file("src/new.pl", "Perl", 10, false),
+                file("src/tool.py", "Python", 10, false),
+            ],
+        );
+        let paths: Vec<_> = violations.iter().filter_map(|v| v.path()).collect();
+        assert_eq!(paths, vec!["scripts/build.py", "src/new.pl"]);
+        assert_eq!(
+            violations[0].to_string(),
+            "scripts/build.py: Python is not allowed in scripts/**"
+        );
+    }
+
+    #[test]
+    fn globs_follow_gitattributes_rules() {
+        let policy = r#"
+deny: [Perl]
+vendored_paths: ["third_party/"]
+paths:
+  - glob: "tools/"
+    allow: [Perl]
+  - glob: "*.t"
+    allow: [Perl]
+"#;
+        let violations = check(
+            policy,
+            vec![
+                file("lib/test/basic.t", "Perl", 10, false),
+                file("node_modules/x/index.js", "JavaScript", 10, true),
+                file("src/old.pl", "Perl", 10, false),
+                file("third_party/zlib/zlib.c", "C", 10, true),
+                file("tools/deep/gen.pl", "Perl", 10, false),
+                file("vendor/third_party/x.c", "C", 10, true),
+            ],
+        );
+        let paths: Vec<_> = violations.iter().filter_map(|v| v.path()).collect();
+        assert_eq!(paths, vec!["node_modules/x/index.js", "src/old.pl"]);
+    }
+
+    #[test]
+    fn groups_match_their_languages() {
+        let violations = check("deny: [TypeScript]", vec![file("a.tsx", "TSX", 10, false)]);
+        assert_eq!(violations.len(), 1);
+    }
+
+    #[test]
+    fn vendored_code_must_be_in_approved_paths() {
+        let violations = check(
+            "deny: [C]\nvendored_paths: [\"third_party/**\"]",
+            vec![
+                file("third_party/zlib/zlib.c", "C", 10, true),
+                file("node_modules/x/index.js", "JavaScript", 10, true),
+            ],
+        );
+        assert_eq!(
+            violations,
+            vec![Violation::VendoredOutsideApprovedPaths {
+                path: PathBuf::from("node_modules/x/index.js"),
+            }]
+        );
+    }
+
+    #[test]
+    fn max_percentage_is_checked_on_totals() {
+        let policy = "max_percentage:\n  JavaScript: 25\n  Rust: 80";
+        let violations = check(
+            policy,
+            vec![
+                file("src/main.rs", "Rust", 70, false),
+                file("web/app.js", "JavaScript", 30, false),
+                file("vendor/lib.js", "JavaScript", 1000, true),
+            ],
+        );
+        assert_eq!(
+            violations,
+            vec![Violation::AboveMaxPercentage {
+                language: "JavaScript",
+                percentage: 30.0,
+                max: 25.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_policies_are_errors() {
+        for policy in [
+            "deny: [not-a-language]",
+            "denyy: [Perl]",
+            "paths:\n  - glob: \"[\"\n    deny: [Perl]",
+            "max_percentage: {Rust: lots}",
+        ] {
+            assert!(
+                matches!(
+                    Policy::from_yaml(policy),
+                    Err(LinguistError::InvalidPolicy(_))
+                ),
+                "{policy}"
+            );
+        }
+    }
+
+    #[test]
+    fn check_command_fails_on_violations() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.path().join(".linguist-policy.yml"), "allow: [Rust]\n").unwrap();
+
+        let run = || {
+            Command::new(env!("CARGO_BIN_EXE_linguist"))
+                .arg("check")
+                .arg(dir.path())
+                .output()
+                .expect("Should run linguist")
+        };
+        let output = run();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"No policy violations\n");
+
+        fs::write(dir.path().join("src/gen.pl"), "print 'hi';\n").unwrap();
+        let output = run();
+        assert_eq!(output.status.code(), Some(1));
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("src/gen.pl: Perl is not allowed by the policy"));
+        assert!(stdout.ends_with("1 violation\n"));
+    }
+}
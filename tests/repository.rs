@@ -0,0 +1,275 @@
+mod test_repository {
+    use linguist::LinguistError;
+    use linguist::gitattributes::GitAttributes;
+    use linguist::repository::{PathFilter, PrunedDir, ScanOptions, analyze, walk};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// Helper: Create a file (and its parent directories) under `root`
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /// Helper: Build a small repository with a mix of languages
+    fn sample_repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "src/main.rs", "fn main() {}\n");
+        write(root, "src/lib.rs", "pub fn lib() {}\n");
+        write(root, "scripts/build.py", "print('hi')\n");
+        write(root, "include/api.h", "#include <iostream>\n");
+        write(
+            root,
+            "node_modules/left-pad/index.js",
+            "module.exports = 1;\n",
+        );
+        write(root, ".git/HEAD", "ref: refs/heads/main\n");
+        write(root, "README", "no language here\n");
+        dir
+    }
+
+    #[test]
+    fn walk_lists_relative_sorted_paths() {
+        let repo = sample_repo();
+        let paths = walk(repo.path()).expect("Should not error");
+        let expected: Vec<PathBuf> = [
+            "README",
+            "include/api.h",
+            "node_modules/left-pad/index.js",
+            "scripts/build.py",
+            "src/lib.rs",
+            "src/main.rs",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    fn breakdown_counts_languages() {
+        let repo = sample_repo();
+        let breakdown = analyze(repo.path(), &ScanOptions::default()).expect("Should not error");
+
+        assert!(breakdown.errors.is_empty());
+        assert_eq!(breakdown.languages["Rust"].files, 2);
+        assert_eq!(breakdown.languages["Rust"].bytes, 29);
+        assert_eq!(breakdown.languages["Python"].files, 1);
+        assert_eq!(breakdown.languages["C++"].files, 1);
+    }
+
+    #[test]
+    fn vendored_files_are_excluded_from_totals() {
+        let repo = sample_repo();
+        let breakdown = analyze(repo.path(), &ScanOptions::default()).expect("Should not error");
+
+        assert!(!breakdown.languages.contains_key("JavaScript"));
+        let vendored: Vec<_> = breakdown.files.iter().filter(|f| f.vendored).collect();
+        assert_eq!(vendored.len(), 1);
+        assert_eq!(vendored[0].language, Some("JavaScript"));
+    }
+
+    #[test]
+    fn results_are_deterministic_across_job_counts() {
+        let repo = sample_repo();
+        for i in 0..50 {
+            write(repo.path(), &format!("gen/file{i:02}.py"), "x = 1\n");
+        }
+
+        let summarize = |jobs| {
+            let options = ScanOptions {
+                jobs,
+                ..ScanOptions::default()
+            };
+            let breakdown = analyze(repo.path(), &options).expect("Should not error");
+            breakdown
+                .files
+                .iter()
+                .map(|f| (f.path.clone(), f.language))
+                .collect::<Vec<_>>()
+        };
+
+        let single = summarize(1);
+        assert_eq!(single, summarize(4));
+        assert_eq!(single, summarize(16));
+    }
+
+    #[test]
+    fn line_counts_are_aggregated_per_language() {
+        let repo = sample_repo();
+        write(
+            repo.path(),
+            "src/util.rs",
+            "// helper
+
+fn util() {}
+",
+        );
+
+        let options = ScanOptions {
+            count_lines: true,
+            ..ScanOptions::default()
+        };
+        let breakdown = analyze(repo.path(), &options).expect("Should not error");
+
+        let rust = breakdown.languages["Rust"].lines;
+        assert_eq!((rust.code, rust.comment, rust.blank), (3, 1, 1));
+        assert!(
+            breakdown
+                .files
+                .iter()
+                .all(|f| f.language.is_none() || f.lines.is_some())
+        );
+    }
+
+    #[test]
+    fn lines_are_not_counted_by_default() {
+        let repo = sample_repo();
+        let breakdown = analyze(repo.path(), &ScanOptions::default()).expect("Should not error");
+        assert!(breakdown.files.iter().all(|f| f.lines.is_none()));
+    }
+
+    #[test]
+    fn filter_and_overrides_apply_to_scans() {
+        let repo = sample_repo();
+        write(repo.path(), "src/generated/schema.rs", "struct Schema;
+");
+        let mut overrides = GitAttributes::new();
+        overrides.add_file("", "scripts/** linguist-vendored
+*.h linguist-language=C
+");
+        let options = ScanOptions {
+            filter: PathFilter::new(&["src/", "*.py", "*.h"], &["src/generated/"])
+                .expect("Should compile"),
+            overrides,
+            ..ScanOptions::default()
+        };
+
+        let breakdown = analyze(repo.path(), &options).expect("Should not error");
+        let paths: Vec<_> = breakdown.files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            paths,
+            ["include/api.h", "scripts/build.py", "src/lib.rs", "src/main.rs"]
+                .iter()
+                .map(PathBuf::from)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(breakdown.files[0].language, Some("C"));
+        assert!(breakdown.files[1].vendored);
+    }
+
+    #[test]
+    fn invalid_filter_globs_are_errors() {
+        for glob in ["", "/", "src/[a"] {
+            assert!(
+                matches!(
+                    PathFilter::new(&[glob], &[]),
+                    Err(LinguistError::InvalidGlob { .. })
+                ),
+                "{glob}"
+            );
+        }
+    }
+
+    /// Helper: Options pruning vendored directories
+    fn pruning(count_pruned_bytes: bool) -> ScanOptions {
+        ScanOptions {
+            prune_vendored: true,
+            count_pruned_bytes,
+            ..ScanOptions::default()
+        }
+    }
+
+    #[test]
+    fn vendored_directories_are_pruned() {
+        let repo = sample_repo();
+        write(
+            repo.path(),
+            "node_modules/left-pad/lib/util.js",
+            "exports.x = 1;\n",
+        );
+        write(repo.path(), "src/vendor.rs", "mod x;\n");
+
+        let breakdown = analyze(repo.path(), &pruning(false)).expect("Should not error");
+        assert!(breakdown.files.iter().all(|f| !f.vendored));
+        assert!(
+            breakdown
+                .files
+                .iter()
+                .any(|f| f.path == Path::new("src/vendor.rs"))
+        );
+        assert_eq!(
+            breakdown.pruned,
+            vec![PrunedDir {
+                path: PathBuf::from("node_modules"),
+                pattern: "(^|/)node_modules/",
+                bytes: None,
+            }]
+        );
+
+        // Totals are the same as when classifying every file
+        let full = analyze(repo.path(), &ScanOptions::default()).expect("Should not error");
+        assert_eq!(breakdown.languages, full.languages);
+        assert!(full.pruned.is_empty());
+    }
+
+    #[test]
+    fn pruned_bytes_can_be_counted() {
+        let repo = sample_repo();
+        write(
+            repo.path(),
+            "node_modules/left-pad/lib/util.js",
+            "exports.x = 1;\n",
+        );
+        let breakdown = analyze(repo.path(), &pruning(true)).expect("Should not error");
+        assert_eq!(breakdown.pruned[0].bytes, Some(20 + 15));
+    }
+
+    #[test]
+    fn unvendored_paths_disable_pruning() {
+        let repo = sample_repo();
+        write(
+            repo.path(),
+            ".gitattributes",
+            "node_modules/left-pad/** -linguist-vendored\n",
+        );
+        let breakdown = analyze(repo.path(), &pruning(false)).expect("Should not error");
+        assert!(breakdown.pruned.is_empty());
+        assert_eq!(breakdown.languages["JavaScript"].files, 1);
+    }
+
+    #[test]
+    fn documentation_directories_are_pruned() {
+        let repo = sample_repo();
+        write(repo.path(), "docs/conf.py", "project = 'x'\n");
+        write(repo.path(), "src/docs.rs", "mod x;\n");
+        write(repo.path(), "src/README.md", "# Notes\n");
+
+        let breakdown = analyze(repo.path(), &pruning(false)).expect("Should not error");
+        assert_eq!(
+            breakdown
+                .pruned
+                .iter()
+                .map(|dir| dir.pattern)
+                .collect::<Vec<_>>(),
+            ["^[Dd]ocs?/", "(^|/)node_modules/"]
+        );
+        // Documentation files outside pruned directories are classified
+        let readme = breakdown
+            .files
+            .iter()
+            .find(|f| f.path == Path::new("src/README.md"))
+            .expect("Should be classified");
+        assert!(readme.documentation);
+
+        // Totals are the same as when classifying every file, which leaves
+        // documentation out
+        let full = analyze(repo.path(), &ScanOptions::default()).expect("Should not error");
+        assert_eq!(breakdown.languages, full.languages);
+        assert_eq!(full.languages["Python"].files, 1);
+        assert_eq!(full.languages["Rust"].files, 3);
+        assert!(!full.languages.contains_key("Markdown"));
+    }
+}
@@ -0,0 +1,218 @@
+mod test_serve {
+    use serde_json::Value;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpStream;
+    use std::path::Path;
+    use std::process::{Child, Command, Stdio};
+
+    /// A `linguist serve` process on a free localhost port, killed on drop
+    struct Server {
+        child: Child,
+        address: String,
+    }
+
+    impl Drop for Server {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+
+    /// Helper: Start the server and wait until it is listening
+    fn start() -> Server {
+        start_in(Path::new("."))
+    }
+
+    /// Helper: Same as `start`, from `dir`
+    fn start_in(dir: &Path) -> Server {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_linguist"))
+            .current_dir(dir)
+            .args(["serve", "--bind", "127.0.0.1:0", "--jobs", "2"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Should start the server");
+
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let address = line
+            .trim()
+            .strip_prefix("Listening on http://")
+            .unwrap_or_else(|| panic!("Unexpected output: {line}"))
+            .to_string();
+        Server { child, address }
+    }
+
+    /// Helper: Send a request, returning the status code and parsed JSON body
+    fn request(server: &Server, method: &str, path: &str, body: &[u8]) -> (u16, Value) {
+        let mut stream = TcpStream::connect(&server.address).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            server.address,
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(body).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    /// Helper: Build a tar archive from (path, content) pairs
+    fn tar(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn detect_uses_filename_and_content() {
+        let server = start();
+        let body =
+            r##"{"filename": "include/foo.h", "content": "#include <iostream>\nclass Foo {};\n"}"##;
+        let (status, report) = request(&server, "POST", "/detect", body.as_bytes());
+
+        assert_eq!(status, 200);
+        assert_eq!(report["path"], "include/foo.h");
+        assert_eq!(report["vendored"], false);
+        let content = report["detections"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|d| d["method"] == "content")
+            .expect("Should have a content detection");
+        assert_eq!(content["languages"], serde_json::json!(["C++"]));
+    }
+
+    #[test]
+    fn detect_rejects_invalid_requests() {
+        let server = start();
+        let (status, report) = request(&server, "POST", "/detect", b"not json");
+        assert_eq!(status, 400);
+        assert!(
+            report["error"]
+                .as_str()
+                .unwrap()
+                .contains("Invalid request")
+        );
+
+        let (status, _) = request(&server, "GET", "/detect", b"");
+        assert_eq!(status, 405);
+    }
+
+    #[test]
+    fn languages_are_looked_up_by_name_or_alias() {
+        let server = start();
+        let (status, language) = request(&server, "GET", "/languages/C%2B%2B", b"");
+        assert_eq!(status, 200);
+        assert_eq!(language["name"], "C++");
+        assert_eq!(language["type"], "programming");
+        assert!(
+            language["extensions"]
+                .as_array()
+                .unwrap()
+                .contains(&".cpp".into())
+        );
+
+        let (_, language) = request(&server, "GET", "/languages/js", b"");
+        assert_eq!(language["name"], "JavaScript");
+
+        let (status, _) = request(&server, "GET", "/languages/not-a-language", b"");
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn breakdown_matches_stats_schema() {
+        let server = start();
+        let archive = tar(&[
+            ("src/main.rs", "fn main() {}\n"),
+            ("src/lib.rs", "// doc\npub fn lib() {}\n"),
+            ("build.py", "print('hi')\n"),
+        ]);
+        let (status, report) = request(&server, "POST", "/breakdown?lines=true", &archive);
+
+        assert_eq!(status, 200);
+        assert_eq!(report["total_bytes"], 13 + 23 + 12);
+        let rust = &report["languages"][0];
+        assert_eq!(rust["name"], "Rust");
+        assert_eq!(rust["bytes"], 36);
+        assert_eq!(rust["files"], 2);
+        assert_eq!(rust["lines"]["comment"], 1);
+        assert_eq!(report["languages"][1]["name"], "Python");
+    }
+
+    #[test]
+    fn project_configuration_is_applied() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".linguist.toml"),
+            "exclude = [\"*.py\"]\n[extensions]\n\".inc\" = \"PHP\"\n",
+        )
+        .unwrap();
+        let server = start_in(dir.path());
+
+        let body = r#"{"filename": "page.inc", "content": "<?php echo 1;\n"}"#;
+        let (status, report) = request(&server, "POST", "/detect", body.as_bytes());
+        assert_eq!(status, 200);
+        assert_eq!(report["detections"][0]["method"], "extension");
+        assert_eq!(
+            report["detections"][0]["languages"],
+            serde_json::json!(["PHP"])
+        );
+
+        let archive = tar(&[
+            ("src/main.rs", "fn main() {}\n"),
+            ("build.py", "print('hi')\n"),
+        ]);
+        let (status, report) = request(&server, "POST", "/breakdown", &archive);
+        assert_eq!(status, 200);
+        assert_eq!(report["languages"].as_array().unwrap().len(), 1);
+        assert_eq!(report["languages"][0]["name"], "Rust");
+    }
+
+    #[test]
+    fn breakdown_rejects_invalid_archives() {
+        let server = start();
+        let mut archive = tar(&[("src/main.rs", "fn main() {}\n")]);
+        archive.truncate(600);
+        let (status, report) = request(&server, "POST", "/breakdown", &archive);
+        assert_eq!(status, 400);
+        assert!(
+            report["error"]
+                .as_str()
+                .unwrap()
+                .contains("Invalid archive")
+        );
+    }
+
+    #[test]
+    fn large_bodies_are_rejected() {
+        let server = start();
+        let body = vec![0; 16 * 1024 * 1024 + 1];
+        for path in ["/detect", "/breakdown"] {
+            let (status, report) = request(&server, "POST", path, &body);
+            assert_eq!(status, 413, "{path}");
+            assert_eq!(report["error"], "Request body too large");
+        }
+    }
+
+    #[test]
+    fn unknown_endpoints_are_not_found() {
+        let server = start();
+        let (status, _) = request(&server, "GET", "/nope", b"");
+        assert_eq!(status, 404);
+    }
+}
@@ -0,0 +1,89 @@
+mod test_stdin {
+    use serde_json::{Value, json};
+    use std::io::Write;
+    use std::process::{Command, Output, Stdio};
+
+    /// Helper: Run `linguist` with the given arguments and content piped to stdin
+    fn run(args: &[&str], input: &str) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_linguist"))
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Should start linguist");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    }
+
+    /// Helper: Run with `--format json`, returning the parsed reports
+    fn run_json(args: &[&str], input: &str) -> Vec<Value> {
+        let output = run(&[&["--format", "json"], args].concat(), input);
+        assert!(output.status.success());
+        serde_json::from_slice(&output.stdout).expect("Should be JSON")
+    }
+
+    /// Helper: The languages a report found by one method
+    fn languages(report: &Value, method: &str) -> Value {
+        report["detections"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|d| d["method"] == method)
+            .map_or(Value::Null, |d| d["languages"].clone())
+    }
+
+    const CPP_HEADER: &str = "#include <iostream>\nclass Foo {};\n";
+
+    #[test]
+    fn stdin_content_is_disambiguated_by_name() {
+        let reports = run_json(&["--stdin", "--name", "include/foo.h"], CPP_HEADER);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0]["path"], "include/foo.h");
+        assert_eq!(
+            languages(&reports[0], "extension"),
+            json!(["C", "C++", "Objective-C"])
+        );
+        assert_eq!(languages(&reports[0], "content"), json!(["C++"]));
+    }
+
+    #[test]
+    fn name_is_checked_for_vendoring() {
+        let reports = run_json(&["--stdin", "--name", "vendor/foo.h"], CPP_HEADER);
+        assert_eq!(reports[0]["vendored"], true);
+    }
+
+    #[test]
+    fn dash_reads_stdin_among_other_files() {
+        let reports = run_json(&["--name", "foo.h", "-", "Cargo.toml"], CPP_HEADER);
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0]["path"], "foo.h");
+        assert_eq!(languages(&reports[0], "content"), json!(["C++"]));
+        assert_eq!(reports[1]["path"], "Cargo.toml");
+        assert_eq!(languages(&reports[1], "extension"), json!(["TOML"]));
+    }
+
+    #[test]
+    fn text_output_uses_the_name() {
+        let output = run(&["--stdin", "--name", "script.py"], "print('hi')\n");
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("script.py: Python (by extension)"));
+    }
+
+    #[test]
+    fn name_requires_stdin_input() {
+        let output = run(&["--name", "foo.h", "Cargo.toml"], "");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("--name can only be used"));
+
+        let output = run(&["--stdin"], "");
+        assert!(!output.status.success());
+    }
+}
@@ -0,0 +1,203 @@
+mod test_stdio {
+    use serde_json::{Value, json};
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::path::Path;
+    use std::process::{Command, Stdio};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    /// Helper: Run `linguist --stdio` with the given input, returning the
+    /// responses keyed by their id
+    fn run(args: &[&str], input: &str) -> HashMap<String, Value> {
+        run_bytes(args, input.as_bytes())
+    }
+
+    /// Helper: Same as `run`, with input that needn't be UTF-8
+    fn run_bytes(args: &[&str], input: &[u8]) -> HashMap<String, Value> {
+        run_in(Path::new("."), args, input)
+    }
+
+    /// Helper: Same as `run_bytes`, started from `dir`
+    fn run_in(dir: &Path, args: &[&str], input: &[u8]) -> HashMap<String, Value> {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_linguist"))
+            .current_dir(dir)
+            .arg("--stdio")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Should start linguist");
+        child.stdin.take().unwrap().write_all(input).unwrap();
+
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let response: Value = serde_json::from_str(line).expect("Should be JSON");
+                (response["id"].to_string(), response)
+            })
+            .collect()
+    }
+
+    /// Helper: The languages a result found by one method
+    fn languages(response: &Value, method: &str) -> Value {
+        response["result"]["detections"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|d| d["method"] == method)
+            .map_or(Value::Null, |d| d["languages"].clone())
+    }
+
+    #[test]
+    fn inline_content_is_disambiguated() {
+        let input = r##"{"id": 1, "path": "include/foo.h", "content": "#include <iostream>\nclass Foo {};\n"}"##;
+        let responses = run(&[], &format!("{input}\n"));
+        assert_eq!(languages(&responses["1"], "content"), json!(["C++"]));
+        assert_eq!(responses["1"]["result"]["path"], "include/foo.h");
+    }
+
+    #[test]
+    fn closed_stdout_stops_the_process() {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_linguist"))
+            .args(["--stdio", "--jobs", "2"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Should start linguist");
+        drop(child.stdout.take());
+
+        // Far more requests than the queue holds, from another thread since a
+        // hung process would block the writes; they fail once it exits
+        let mut stdin = child.stdin.take().unwrap();
+        thread::spawn(move || {
+            let request = "{\"id\": 1, \"path\": \"main.py\", \"content\": \"\"}\n";
+            for _ in 0..10_000 {
+                if stdin.write_all(request.as_bytes()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(30);
+        let status = loop {
+            if let Some(status) = child.try_wait().unwrap() {
+                break status;
+            }
+            if Instant::now() > deadline {
+                child.kill().unwrap();
+                panic!("linguist --stdio kept running with its stdout closed");
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert!(!status.success());
+    }
+
+    #[test]
+    fn project_configuration_is_applied() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".linguist.toml"),
+            "[extensions]\n\".inc\" = \"PHP\"\n",
+        )
+        .unwrap();
+
+        let input = r#"{"id": 1, "path": "page.inc", "content": "<?php echo 1;\n"}"#;
+        let responses = run_in(dir.path(), &[], format!("{input}\n").as_bytes());
+        assert_eq!(languages(&responses["1"], "extension"), json!(["PHP"]));
+    }
+
+    #[test]
+    fn content_is_read_from_disk_when_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("script.rb");
+        std::fs::write(&path, "#!/usr/bin/env ruby\nputs 'hi'\n").unwrap();
+
+        let input = json!({"id": "disk", "path": path}).to_string();
+        let responses = run(&[], &format!("{input}\n"));
+        assert_eq!(
+            languages(&responses["\"disk\""], "extension"),
+            json!(["Ruby"])
+        );
+    }
+
+    #[test]
+    fn every_request_is_answered_with_its_id() {
+        let input: String = (0..200)
+            .map(|i| format!("{{\"id\": {i}, \"path\": \"file{i}.py\", \"content\": \"\"}}\n\n"))
+            .collect();
+        let responses = run(&["--jobs", "4"], &input);
+
+        assert_eq!(responses.len(), 200);
+        for i in 0..200 {
+            let response = &responses[&i.to_string()];
+            assert_eq!(response["result"]["path"], format!("file{i}.py"));
+            assert_eq!(languages(response, "extension"), json!(["Python"]));
+        }
+    }
+
+    #[test]
+    fn invalid_requests_get_errors() {
+        let input = "not json\n{\"id\": 7, \"content\": \"x\"}\n";
+        let responses = run(&[], input);
+
+        assert!(
+            responses["null"]["error"]
+                .as_str()
+                .unwrap()
+                .contains("Invalid request")
+        );
+        assert!(
+            responses["7"]["error"]
+                .as_str()
+                .unwrap()
+                .contains("missing field `path`")
+        );
+        assert!(responses["7"].get("result").is_none());
+    }
+
+    #[test]
+    fn invalid_utf8_lines_get_errors() {
+        let mut input = b"{\"id\": 1, \"path\": \"caf\xe9.rs\"}\n".to_vec();
+        input.extend_from_slice(b"{\"id\": 2, \"path\": \"main.py\", \"content\": \"\"}\n");
+        let responses = run_bytes(&[], &input);
+
+        assert_eq!(responses.len(), 2);
+        assert!(
+            responses["null"]["error"]
+                .as_str()
+                .unwrap()
+                .contains("Invalid request")
+        );
+        assert_eq!(languages(&responses["2"], "extension"), json!(["Python"]));
+    }
+
+    #[test]
+    fn detection_method_flags_apply() {
+        let input = r#"{"id": 1, "path": "foo.h", "content": "class Foo {};"}"#;
+        let responses = run(&["--by-extension"], &format!("{input}\n"));
+        let detections = responses["1"]["result"]["detections"].as_array().unwrap();
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0]["method"], "extension");
+    }
+
+    #[test]
+    fn exceeded_heuristic_limits_are_warnings() {
+        let input = r#"{"id": 1, "path": "page.tpl", "content": "{$title}"}"#;
+        let responses = run(&["--backtrack-limit", "1"], &format!("{input}\n"));
+        assert_eq!(languages(&responses["1"], "content"), Value::Null);
+        let warnings = responses["1"]["result"]["warnings"].as_array().unwrap();
+        assert!(!warnings.is_empty());
+        assert!(
+            warnings[0].as_str().unwrap().contains("gave up"),
+            "{warnings:?}"
+        );
+
+        let responses = run(&[], &format!("{input}\n"));
+        assert_eq!(languages(&responses["1"], "content"), json!(["Smarty"]));
+    }
+}
@@ -0,0 +1,218 @@
+mod test_strategy {
+    use linguist::repository::{ScanOptions, analyze};
+    use linguist::strategy::{Heuristics, Pipeline, Strategy};
+    use linguist::{
+        DetectedLanguage, HeuristicLimits, LinguistError, Result, detect_reader, find_language,
+    };
+    use std::fs;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// Helper: Tells YAML dialects apart by a `# dialect: <name>` header line
+    struct DialectHeader;
+
+    impl Strategy for DialectHeader {
+        fn name(&self) -> &str {
+            "dialect-header"
+        }
+
+        fn needs_content(&self) -> bool {
+            true
+        }
+
+        fn call(
+            &self,
+            _path: &Path,
+            content: Option<&str>,
+            candidates: &[DetectedLanguage],
+        ) -> Result<Vec<DetectedLanguage>> {
+            let dialect = content
+                .and_then(|content| content.lines().next())
+                .and_then(|line| line.strip_prefix("# dialect: "));
+            Ok(candidates
+                .iter()
+                .filter(|lang| dialect.is_some_and(|d| lang.name.eq_ignore_ascii_case(d)))
+                .cloned()
+                .collect())
+        }
+    }
+
+    /// Helper: Claims `.raml.yml` files as RAML, counting its calls and
+    /// recording the content it was given
+    #[derive(Default)]
+    struct Recorder {
+        needs_content: bool,
+        calls: AtomicUsize,
+        content: Mutex<Vec<Option<String>>>,
+    }
+
+    impl Strategy for Recorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        fn needs_content(&self) -> bool {
+            self.needs_content
+        }
+
+        fn call(
+            &self,
+            path: &Path,
+            content: Option<&str>,
+            _candidates: &[DetectedLanguage],
+        ) -> Result<Vec<DetectedLanguage>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.content.lock().unwrap().push(content.map(String::from));
+            let raml = path
+                .to_str()
+                .is_some_and(|path| path.ends_with(".raml.yml"));
+            Ok(find_language("RAML").filter(|_| raml).into_iter().collect())
+        }
+    }
+
+    /// Helper: The names of the languages a pipeline detects for some content
+    fn detect(pipeline: &Pipeline, path: &str, content: &str) -> Vec<&'static str> {
+        pipeline
+            .detect(path, content.as_bytes(), 1024)
+            .expect("Should not error")
+            .iter()
+            .map(|lang| lang.name)
+            .collect()
+    }
+
+    #[test]
+    fn default_pipeline_matches_detect_reader() {
+        let pipeline = Pipeline::default();
+        assert_eq!(
+            pipeline.names(),
+            vec!["filename", "extension", "shebang", "heuristics"]
+        );
+
+        for (path, content) in [
+            ("Makefile", "all:\n"),
+            ("main.py", "print('hi')\n"),
+            ("foo.h", "#include <iostream>\nclass Foo {};\n"),
+            ("foo.h", "int main(void);\n"),
+            ("run", "#!/usr/bin/env python3\nprint('hi')\n"),
+            ("openapi.yml", "openapi: 3.0.0\n"),
+            ("unknown", "nothing to see here\n"),
+        ] {
+            let expected: Vec<&str> = detect_reader(path, content.as_bytes())
+                .unwrap()
+                .iter()
+                .map(|lang| lang.name)
+                .collect();
+            assert_eq!(detect(&pipeline, path, content), expected, "{path}");
+        }
+    }
+
+    #[test]
+    fn custom_strategy_narrows_candidates() {
+        let mut pipeline = Pipeline::default();
+        let index = pipeline.position("heuristics").unwrap();
+        pipeline.strategies.insert(index, Box::new(DialectHeader));
+
+        let content = "# dialect: MiniYAML\nkey: value\n";
+        assert_eq!(detect(&pipeline, "mod.yml", content), vec!["MiniYAML"]);
+
+        // Without the header, the heuristics still decide
+        assert_eq!(detect(&pipeline, "mod.yml", "key: value\n"), vec!["YAML"]);
+        assert_eq!(
+            detect(&Pipeline::default(), "mod.yml", content),
+            vec!["YAML"]
+        );
+    }
+
+    #[test]
+    fn strategies_after_a_single_candidate_are_skipped() {
+        let recorder = Arc::new(Recorder::default());
+        let mut pipeline = Pipeline::default();
+        pipeline.strategies.push(Box::new(recorder.clone()));
+
+        assert_eq!(detect(&pipeline, "main.py", ""), vec!["Python"]);
+        assert_eq!(recorder.calls.load(Ordering::SeqCst), 0);
+
+        // Headers without C++ features are left to the C fallback rule
+        let content = "struct foo;\n";
+        assert_eq!(detect(&pipeline, "foo.h", content), vec!["C"]);
+        assert_eq!(recorder.calls.load(Ordering::SeqCst), 0);
+
+        detect(&pipeline, "api.raml.yml", "title: API\n");
+        assert_eq!(recorder.calls.load(Ordering::SeqCst), 0);
+
+        // Nothing else knows this file
+        assert!(detect(&pipeline, "notes.unknown", "").is_empty());
+        assert_eq!(recorder.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn content_is_read_before_the_first_strategy_needing_it() {
+        for needs_content in [false, true] {
+            let recorder = Arc::new(Recorder {
+                needs_content,
+                ..Recorder::default()
+            });
+            let mut pipeline = Pipeline::default();
+            pipeline.strategies.insert(0, Box::new(recorder.clone()));
+
+            assert_eq!(
+                detect(&pipeline, "api.raml.yml", "title: API\n"),
+                vec!["RAML"]
+            );
+            let expected = needs_content.then(|| "title: API\n".to_string());
+            assert_eq!(*recorder.content.lock().unwrap(), vec![expected]);
+        }
+    }
+
+    #[test]
+    fn warnings_of_strategies_are_collected() {
+        let limits = HeuristicLimits {
+            backtrack_limit: 1,
+            ..HeuristicLimits::default()
+        };
+        let content = "{$title}\n";
+
+        // Also through a shared strategy
+        let shared = Pipeline {
+            strategies: vec![Box::new(Arc::new(Heuristics { limits }))],
+        };
+        for pipeline in [Pipeline::with_heuristic_limits(limits), shared] {
+            let outcome = pipeline
+                .detect_with_warnings("page.tpl", content.as_bytes(), 1024)
+                .expect("Should not error");
+            assert!(!outcome.warnings.is_empty());
+            assert!(
+                outcome
+                    .warnings
+                    .iter()
+                    .all(|e| matches!(e, LinguistError::RegexLimitExceeded { .. }))
+            );
+        }
+
+        let outcome = Pipeline::default()
+            .detect_with_warnings("page.tpl", content.as_bytes(), 1024)
+            .expect("Should not error");
+        assert_eq!(outcome.languages[0].name, "Smarty");
+        assert!(outcome.warnings.is_empty());
+    }
+
+    #[test]
+    fn scans_use_the_configured_pipeline() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("mod.yml"), "# dialect: MiniYAML\n").unwrap();
+        fs::write(dir.path().join("ci.yml"), "on: push\n").unwrap();
+
+        let mut pipeline = Pipeline::default();
+        let index = pipeline.position("heuristics").unwrap();
+        pipeline.strategies.insert(index, Box::new(DialectHeader));
+        let options = ScanOptions {
+            pipeline: Some(Arc::new(pipeline)),
+            ..ScanOptions::default()
+        };
+
+        let breakdown = analyze(dir.path(), &options).expect("Should not error");
+        assert_eq!(breakdown.languages["MiniYAML"].files, 1);
+        assert_eq!(breakdown.languages["YAML"].files, 1);
+    }
+}
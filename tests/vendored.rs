@@ -1,5 +1,8 @@
 mod test_vendored {
-    use linguist::is_vendored;
+    use linguist::definitions::VENDOR;
+    use linguist::vendor::VendorMatcher;
+    use linguist::{LinguistError, is_vendored, vendored_pattern};
+    use std::process::Command;
 
     /// Helper: Assert that a file is vendored (expects no error)
     fn assert_vendored(path: &str) {
@@ -104,4 +107,143 @@ mod test_vendored {
         assert_not_vendored("main.js");
         assert_not_vendored("index.html");
     }
+
+    #[test]
+    fn root_relative_matching() {
+        let root = "/home/ci/repo";
+        assert_eq!(
+            vendored_pattern(root, "/home/ci/repo/deps/zlib/zlib.c").unwrap(),
+            Some("^deps/")
+        );
+        assert_eq!(
+            vendored_pattern(root, "deps/zlib/zlib.c").unwrap(),
+            Some("^deps/")
+        );
+        assert_eq!(
+            vendored_pattern(root, "./Dependencies/a.c").unwrap(),
+            Some("^[Dd]ependencies/")
+        );
+        assert_eq!(vendored_pattern(root, "src/deps/zlib.c").unwrap(), None);
+
+        // The raw absolute path doesn't match anchored patterns
+        assert_not_vendored("/home/ci/repo/deps/zlib/zlib.c");
+    }
+
+    #[test]
+    fn relative_roots_are_stripped() {
+        assert_eq!(
+            vendored_pattern("repo", "repo/node_modules/a/index.js").unwrap(),
+            Some("(^|/)node_modules/")
+        );
+        assert_eq!(
+            vendored_pattern("repo", "repo/deps/x.c").unwrap(),
+            Some("^deps/")
+        );
+    }
+
+    #[test]
+    fn paths_outside_the_root_are_errors() {
+        for path in ["/elsewhere/deps/x.c", "../deps/x.c", "deps/../../x.c"] {
+            assert!(
+                matches!(
+                    vendored_pattern("/home/ci/repo", path),
+                    Err(LinguistError::InvalidPath(_))
+                ),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn explain_prints_the_matching_pattern() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = Command::new(env!("CARGO_BIN_EXE_linguist"))
+            .current_dir(dir.path())
+            .args(["--explain", "-e", "--no-config"])
+            .arg(dir.path().join("deps/zlib.c"))
+            .arg("src/main.py")
+            .output()
+            .expect("Should run linguist");
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let lines: Vec<_> = stdout.lines().collect();
+        assert!(lines[0].ends_with("deps/zlib.c: C (by extension) [vendored]"));
+        assert!(lines[1].ends_with("deps/zlib.c: vendored, matches vendor.yml pattern ^deps/"));
+        assert_eq!(lines[3], "src/main.py: not vendored");
+    }
+
+    /// Helper: Compile a matcher, expecting every pattern to be valid
+    fn matcher(patterns: &[&str]) -> VendorMatcher {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        let (matcher, errors) = VendorMatcher::new(&patterns);
+        assert!(errors.is_empty(), "{errors:?}");
+        matcher
+    }
+
+    #[test]
+    fn matches_agree_with_testing_each_pattern_in_turn() {
+        let regexes: Vec<_> = VENDOR
+            .iter()
+            .map(|p| fancy_regex::Regex::new(p).unwrap())
+            .collect();
+        for path in [
+            "node_modules/react/index.js",
+            "deps/zlib/zlib.c",
+            "src/deps/zlib.c",
+            "public/js/jquery-3.7.1.min.js",
+            "static/bootstrap.css",
+            "Godeps/_workspace/src/x.go",
+            "Pods/AFNetworking/AFURLSession.m",
+            "dist/app.js",
+            "docs/conf.py",
+            "src/main.rs",
+            "README.md",
+            "",
+        ] {
+            let expected = regexes
+                .iter()
+                .find(|r| r.is_match(path).unwrap())
+                .map(|r| r.as_str());
+            assert_eq!(vendored_pattern("", path).unwrap(), expected, "{path}");
+        }
+    }
+
+    #[test]
+    fn first_pattern_in_order_wins() {
+        let matcher = matcher(&["\\.min\\.js$", "(^|/)vendor/", "^[^/]+$", "(?=x)x{2}"]);
+        assert_eq!(
+            matcher.first_match("vendor/a.min.js").unwrap(),
+            Some("\\.min\\.js$")
+        );
+        assert_eq!(
+            matcher.first_match("vendor/a.js").unwrap(),
+            Some("(^|/)vendor/")
+        );
+        assert_eq!(matcher.first_match("a.js").unwrap(), Some("^[^/]+$"));
+        assert_eq!(matcher.first_match("src/xx").unwrap(), Some("(?=x)x{2}"));
+        assert_eq!(matcher.first_match("src/a.js").unwrap(), None);
+    }
+
+    #[test]
+    fn invalid_patterns_are_reported() {
+        let patterns = vec!["(^|/)vendor/".to_string(), "([a-".to_string()];
+        let (matcher, errors) = VendorMatcher::new(&patterns);
+        assert!(matches!(errors[..], [LinguistError::InvalidRegex { .. }]));
+        assert_eq!(
+            matcher.first_match("vendor/a.js").unwrap(),
+            Some("(^|/)vendor/")
+        );
+    }
+
+    #[test]
+    fn regex_runtime_errors_are_surfaced() {
+        // The backreference needs fancy_regex, which gives up backtracking
+        let matcher = matcher(&["^(a+)+\\1b$"]);
+        let path = "a".repeat(40);
+        assert!(matches!(
+            matcher.first_match(&path),
+            Err(LinguistError::RegexLimitExceeded { .. })
+        ));
+    }
 }